@@ -0,0 +1,299 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module prepares the samples of the sounds: it generates the sine tones and decodes
+//! the WAV and OGG files. All sounds are converted to mono at the sample rate of the device.
+
+use std::path::Path;
+
+/// The length of the fade at both ends of a tone in seconds, which prevents clicks.
+const FADE_SECONDS: f32 = 0.005;
+
+/// Generates a sine tone.
+///
+/// # Arguments
+///
+/// * `frequency` - The frequency of the tone in Hz.
+/// * `duration` - The duration of the tone in milliseconds.
+/// * `sample_rate` - The sample rate of the device.
+///
+/// # Returns
+///
+/// * The samples of the tone.
+pub fn tone(frequency: f32, duration: u64, sample_rate: u32) -> Vec<f32> {
+    let rate = sample_rate as f32;
+    let length = (duration as f32 / 1000.0 * rate) as usize;
+    let fade = (FADE_SECONDS * rate).max(1.0);
+
+    (0..length)
+        .map(|i| {
+            let envelope = (i as f32 / fade).min((length - i) as f32 / fade).min(1.0);
+            (i as f32 / rate * frequency * 2.0 * std::f32::consts::PI).sin() * 0.5 * envelope
+        })
+        .collect()
+}
+
+/// Loads a sound file by its extension.
+///
+/// # Arguments
+///
+/// * `path` - The path of a WAV or OGG file.
+/// * `sample_rate` - The sample rate of the device.
+///
+/// # Returns
+///
+/// * `Result<Vec<f32>, String>` - The samples of the sound, or an error message.
+pub fn load(path: &Path, sample_rate: u32) -> Result<Vec<f32>, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let (samples, channels, rate) = match extension.as_str() {
+        "wav" => decode_wav(&std::fs::read(path).map_err(|e| e.to_string())?)?,
+        "ogg" => decode_ogg(path)?,
+        _ => return Err(format!("unsupported sound file {:?}", path)),
+    };
+    Ok(resample(&mono(&samples, channels), rate, sample_rate))
+}
+
+/// Decodes an uncompressed WAV file.
+///
+/// # Arguments
+///
+/// * `data` - The content of the file.
+///
+/// # Returns
+///
+/// * `Result<(Vec<f32>, usize, u32), String>` - The interleaved samples, the number of channels
+///   and the sample rate, or an error message.
+fn decode_wav(data: &[u8]) -> Result<(Vec<f32>, usize, u32), String> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err("not a WAV file".to_string());
+    }
+
+    let u16_at = |bytes: &[u8], i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u32_at = |bytes: &[u8], i: usize| {
+        u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+    };
+
+    // The format and the samples are in separate chunks
+    let mut format = None;
+    let mut samples = None;
+    let mut position = 12;
+    while position + 8 <= data.len() {
+        let id = &data[position..position + 4];
+        let size = u32_at(data, position + 4) as usize;
+        let start = position + 8;
+        let end = start.saturating_add(size).min(data.len());
+        // The last chunk may be cut off in a truncated file
+        let body = &data[start..end];
+        match id {
+            b"fmt " if size >= 16 => {
+                if body.len() < 16 {
+                    return Err("truncated format chunk".to_string());
+                }
+                let mut tag = u16_at(body, 0);
+                // The extensible format keeps the real tag in its sub-format
+                if tag == 0xfffe && size >= 26 {
+                    if body.len() < 26 {
+                        return Err("truncated format chunk".to_string());
+                    }
+                    tag = u16_at(body, 24);
+                }
+                format = Some((
+                    tag,
+                    u16_at(body, 2) as usize,
+                    u32_at(body, 4),
+                    u16_at(body, 14),
+                ));
+            }
+            b"data" => samples = Some(body),
+            _ => (),
+        }
+        // A truncated chunk is the last one
+        if end - start < size {
+            break;
+        }
+        // Chunks are aligned to two bytes
+        position = end + size % 2;
+    }
+
+    let (tag, channels, rate, bits) = format.ok_or("no format chunk")?;
+    let samples = samples.ok_or("no data chunk")?;
+    if channels == 0 {
+        return Err("no channels".to_string());
+    }
+
+    let decoded: Vec<f32> = match (tag, bits) {
+        (1, 8) => samples
+            .iter()
+            .map(|s| (*s as f32 - 128.0) / 128.0)
+            .collect(),
+        (1, 16) => samples
+            .chunks_exact(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)
+            .collect(),
+        (1, 24) => samples
+            .chunks_exact(3)
+            .map(|s| i32::from_le_bytes([0, s[0], s[1], s[2]]) as f32 / 2147483648.0)
+            .collect(),
+        (1, 32) => samples
+            .chunks_exact(4)
+            .map(|s| i32::from_le_bytes([s[0], s[1], s[2], s[3]]) as f32 / 2147483648.0)
+            .collect(),
+        (3, 32) => samples
+            .chunks_exact(4)
+            .map(|s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+            .collect(),
+        _ => return Err(format!("unsupported WAV format {} with {} bits", tag, bits)),
+    };
+
+    Ok((decoded, channels, rate))
+}
+
+/// Decodes an OGG Vorbis file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// * `Result<(Vec<f32>, usize, u32), String>` - The interleaved samples, the number of channels
+///   and the sample rate, or an error message.
+fn decode_ogg(path: &Path) -> Result<(Vec<f32>, usize, u32), String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(std::io::BufReader::new(file))
+        .map_err(|e| e.to_string())?;
+    let channels = reader.ident_hdr.audio_channels as usize;
+    let rate = reader.ident_hdr.audio_sample_rate;
+
+    let mut samples = Vec::new();
+    while let Some(packet) = reader.read_dec_packet_itl().map_err(|e| e.to_string())? {
+        samples.extend(packet.into_iter().map(|s| s as f32 / 32768.0));
+    }
+
+    Ok((samples, channels, rate))
+}
+
+/// Mixes the channels into one.
+///
+/// # Arguments
+///
+/// * `samples` - The interleaved samples.
+/// * `channels` - The number of channels.
+///
+/// # Returns
+///
+/// * The mono samples.
+fn mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    samples
+        .chunks_exact(channels.max(1))
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Converts the samples to another sample rate with linear interpolation.
+///
+/// # Arguments
+///
+/// * `samples` - The mono samples.
+/// * `from` - The sample rate of the samples.
+/// * `to` - The sample rate of the device.
+///
+/// # Returns
+///
+/// * The converted samples.
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || from == 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let step = from as f64 / to as f64;
+    let length = (samples.len() as f64 / step) as usize;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let current = samples[index.min(samples.len() - 1)];
+            let next = samples[(index + 1).min(samples.len() - 1)];
+            current + (next - current) * fraction
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a WAV file with 16-bit samples, the format chunk cut to `format_length` bytes.
+    fn wav(format_length: usize, samples: &[i16]) -> Vec<u8> {
+        let mut format = Vec::new();
+        format.extend_from_slice(&1u16.to_le_bytes());
+        format.extend_from_slice(&1u16.to_le_bytes());
+        format.extend_from_slice(&8000u32.to_le_bytes());
+        format.extend_from_slice(&16000u32.to_le_bytes());
+        format.extend_from_slice(&2u16.to_le_bytes());
+        format.extend_from_slice(&16u16.to_le_bytes());
+
+        let mut data = b"RIFF\0\0\0\0WAVE".to_vec();
+        if format_length < format.len() {
+            // The file ends inside the format chunk
+            data.extend_from_slice(b"fmt ");
+            data.extend_from_slice(&16u32.to_le_bytes());
+            data.extend_from_slice(&format[..format_length]);
+            return data;
+        }
+        data.extend_from_slice(b"fmt ");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&format);
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&((samples.len() * 2) as u32).to_le_bytes());
+        for sample in samples {
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn decodes_16_bit_wav() {
+        let (samples, channels, rate) = decode_wav(&wav(16, &[0, 16384, -32768])).unwrap();
+        assert_eq!(channels, 1);
+        assert_eq!(rate, 8000);
+        assert_eq!(samples, vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn rejects_truncated_format_chunk() {
+        for length in 0..16 {
+            assert!(decode_wav(&wav(length, &[])).is_err());
+        }
+    }
+
+    #[test]
+    fn keeps_samples_of_truncated_data_chunk() {
+        let mut data = wav(16, &[100, 200, 300]);
+        data.truncate(data.len() - 3);
+        let (samples, _, _) = decode_wav(&data).unwrap();
+        assert_eq!(samples.len(), 1);
+    }
+}
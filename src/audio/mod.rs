@@ -0,0 +1,394 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the audio service, which plays the sounds of the alerts in its own
+//! thread. The parsers only request an alert to be played, so the log processing is never
+//! blocked by the sound, and a missing audio device only makes the alerts silent.
+
+mod decode;
+mod output;
+
+use crate::config::{AudioConfig, SoundConfig};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// Alerts older than this are not played, e.g. when the log is read from the beginning.
+const STALE_AFTER_SECONDS: i64 = 60;
+
+/// Enum representing an alert which has a sound.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Alert {
+    /// Another player has joined the match.
+    PlayerJoined,
+    /// Another player has left the match.
+    PlayerLeft,
+    /// The first player outside the party has come near.
+    FirstContact,
+    /// More players have come near since the last contact.
+    MoreContacts,
+    /// An alert named in the configuration, e.g. a countdown alert.
+    Custom(String),
+}
+
+impl Alert {
+    /// All built-in alerts.
+    pub const ALL: [Alert; 4] = [
+        Alert::PlayerJoined,
+        Alert::PlayerLeft,
+        Alert::FirstContact,
+        Alert::MoreContacts,
+    ];
+
+    /// Returns the name of the alert in the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * The name in snake case.
+    pub fn name(&self) -> &str {
+        match self {
+            Alert::PlayerJoined => "player_joined",
+            Alert::PlayerLeft => "player_left",
+            Alert::FirstContact => "first_contact",
+            Alert::MoreContacts => "more_contacts",
+            Alert::Custom(name) => name,
+        }
+    }
+
+    /// Returns the built-in sound of the alert.
+    ///
+    /// # Returns
+    ///
+    /// * The sound used if the configuration doesn't override it.
+    fn default_sound(&self) -> SoundConfig {
+        match self {
+            Alert::PlayerJoined => SoundConfig::Tone {
+                frequency: 2000.0,
+                duration: 250,
+            },
+            Alert::PlayerLeft => SoundConfig::Tone {
+                frequency: 400.0,
+                duration: 150,
+            },
+            Alert::FirstContact => SoundConfig::Tone {
+                frequency: 1200.0,
+                duration: 400,
+            },
+            Alert::MoreContacts => SoundConfig::Tone {
+                frequency: 1600.0,
+                duration: 120,
+            },
+            Alert::Custom(_) => SoundConfig::Tone {
+                frequency: 1000.0,
+                duration: 300,
+            },
+        }
+    }
+}
+
+/// Enum representing a request to the audio service.
+#[derive(Debug)]
+enum Request {
+    /// Plays the sound of the alert.
+    Play(Alert),
+    /// Changes the master volume, from 0 to 1.
+    SetVolume(f32),
+    /// Mutes or unmutes all sounds, or toggles the mute if None.
+    SetMuted(Option<bool>),
+}
+
+/// The `Audio` struct is a handle of the audio service, which can be shared by the parsers.
+#[derive(Debug, Clone)]
+pub struct Audio {
+    /// The queue of the requests to the service thread.
+    sender: mpsc::Sender<Request>,
+}
+
+impl Audio {
+    /// Starts the audio service in its own thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The audio section of the configuration.
+    /// * `base` - The directory the paths of the sound files are relative to.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A handle of the started service.
+    pub fn start(config: &AudioConfig, base: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let config = config.clone();
+        let base = base.to_path_buf();
+
+        // The output stream cannot be moved between threads on all platforms, so it is
+        // opened and kept in the service thread
+        let spawned = std::thread::Builder::new()
+            .name("audio".to_string())
+            .spawn(move || Service::new(config, base).run(receiver));
+        if let Err(e) = spawned {
+            log::error!("Cannot start the audio service: {}", e);
+        }
+
+        Self { sender }
+    }
+
+    /// Creates a handle which plays nothing, e.g. for the screenshots.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A handle without the service.
+    pub fn silent() -> Self {
+        // The requests are dropped together with the receiver
+        let (sender, _) = mpsc::channel();
+        Self { sender }
+    }
+
+    /// Requests the sound of the alert, unless the alert is stale.
+    ///
+    /// # Arguments
+    ///
+    /// * `alert` - The alert to play.
+    /// * `time` - The time of the event in the game log.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn play(&self, alert: Alert, time: chrono::DateTime<chrono::Utc>) {
+        if crate::utils::now() - time < chrono::Duration::seconds(STALE_AFTER_SECONDS) {
+            self.send(Request::Play(alert));
+        }
+    }
+
+    /// Changes the master volume.
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - The volume from 0 to 1.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn set_volume(&self, volume: f32) {
+        self.send(Request::SetVolume(volume));
+    }
+
+    /// Mutes or unmutes all sounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `muted` - Whether the sounds are muted, or None to toggle it.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn set_muted(&self, muted: Option<bool>) {
+        self.send(Request::SetMuted(muted));
+    }
+
+    /// Sends a request to the service thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn send(&self, request: Request) {
+        // The service only stops if its thread could not be started, which is already logged
+        self.sender.send(request).ok();
+    }
+}
+
+/// The `Service` struct plays the requested sounds in the service thread.
+struct Service {
+    /// The audio configuration.
+    config: AudioConfig,
+    /// The directory the paths of the sound files are relative to.
+    base: PathBuf,
+    /// The master volume from 0 to 1.
+    volume: f32,
+    /// Whether all sounds are muted.
+    muted: bool,
+    /// The moments the alerts were played last, for the rate limiting.
+    last_played: HashMap<Alert, Instant>,
+}
+
+impl Service {
+    /// Creates a new `Service` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The audio section of the configuration.
+    /// * `base` - The directory the paths of the sound files are relative to.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Service`.
+    fn new(config: AudioConfig, base: PathBuf) -> Self {
+        Self {
+            volume: config.volume.clamp(0.0, 1.0),
+            muted: config.muted,
+            config,
+            base,
+            last_played: HashMap::new(),
+        }
+    }
+
+    /// Handles the requests until all handles are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver` - The queue of the requests.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn run(mut self, receiver: mpsc::Receiver<Request>) {
+        // Without an output device the requests are still accepted, but nothing is played
+        let output = match output::Output::open() {
+            Ok(output) => Some(output),
+            Err(e) => {
+                log::warn!("No audio output ({}), the alerts are silent", e);
+                None
+            }
+        };
+        let mut sounds = output
+            .as_ref()
+            .map(|output| self.load_sounds(output.sample_rate()))
+            .unwrap_or_default();
+
+        for request in receiver {
+            match request {
+                Request::Play(alert) => {
+                    if self.muted || !self.ready(&alert) {
+                        continue;
+                    }
+                    let output = match &output {
+                        Some(output) => output,
+                        None => continue,
+                    };
+                    // The custom alerts without a configured sound use the built-in tone
+                    if !sounds.contains_key(&alert) {
+                        if let Some(sound) = self.load_sound(&alert, output.sample_rate()) {
+                            sounds.insert(alert.clone(), sound);
+                        }
+                    }
+                    if let Some(sound) = sounds.get(&alert) {
+                        output.play(sound.clone(), self.volume);
+                    }
+                }
+                Request::SetVolume(volume) => {
+                    self.volume = volume.clamp(0.0, 1.0);
+                    log::info!("Volume: {:.0}%", self.volume * 100.0);
+                }
+                Request::SetMuted(muted) => {
+                    self.muted = muted.unwrap_or(!self.muted);
+                    log::info!("Sounds muted: {}", self.muted);
+                }
+            }
+        }
+    }
+
+    /// Checks the rate limit of the alert and marks it as played.
+    ///
+    /// # Arguments
+    ///
+    /// * `alert` - The alert to play.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the alert has not been played recently.
+    fn ready(&mut self, alert: &Alert) -> bool {
+        let now = Instant::now();
+        let interval = Duration::from_millis(self.config.min_interval);
+        match self.last_played.get(alert) {
+            Some(last) if now.duration_since(*last) < interval => false,
+            _ => {
+                self.last_played.insert(alert.clone(), now);
+                true
+            }
+        }
+    }
+
+    /// Prepares the sounds of the built-in alerts and of all alerts named in the configuration
+    /// for the output device.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - The sample rate of the output device.
+    ///
+    /// # Returns
+    ///
+    /// * The samples of the sounds by their alerts.
+    fn load_sounds(&self, sample_rate: u32) -> HashMap<Alert, Arc<Vec<f32>>> {
+        // Names other than the built-in alerts belong to the custom alerts
+        let custom = self
+            .config
+            .sounds
+            .keys()
+            .filter(|name| !Alert::ALL.iter().any(|alert| alert.name() == name.as_str()))
+            .map(|name| Alert::Custom(name.clone()));
+
+        Alert::ALL
+            .into_iter()
+            .chain(custom)
+            .filter_map(|alert| {
+                let sound = self.load_sound(&alert, sample_rate)?;
+                Some((alert, sound))
+            })
+            .collect()
+    }
+
+    /// Prepares the sound of the alert for the output device.
+    ///
+    /// # Arguments
+    ///
+    /// * `alert` - The alert.
+    /// * `sample_rate` - The sample rate of the output device.
+    ///
+    /// # Returns
+    ///
+    /// * The samples of the sound, or None if it cannot be loaded.
+    fn load_sound(&self, alert: &Alert, sample_rate: u32) -> Option<Arc<Vec<f32>>> {
+        let sound = self
+            .config
+            .sounds
+            .get(alert.name())
+            .cloned()
+            .unwrap_or_else(|| alert.default_sound());
+        let samples = match sound {
+            SoundConfig::Tone {
+                frequency,
+                duration,
+            } => Ok(decode::tone(frequency, duration, sample_rate)),
+            SoundConfig::File { file } => decode::load(&self.base.join(file), sample_rate),
+        };
+        match samples {
+            Ok(samples) => Some(Arc::new(samples)),
+            Err(e) => {
+                log::error!("Cannot load the sound of {}: {}", alert.name(), e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the output stream of the audio service, which mixes the playing sounds.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+use std::sync::{Arc, Mutex};
+
+/// The maximum number of sounds playing at once, the oldest one is stopped for a new one.
+const MAX_VOICES: usize = 4;
+
+/// The `Voice` struct is a sound being played.
+struct Voice {
+    /// The mono samples of the sound at the sample rate of the device.
+    samples: Arc<Vec<f32>>,
+    /// The position of the next sample.
+    position: usize,
+    /// The volume of the sound.
+    gain: f32,
+}
+
+/// The `Output` struct holds the stream of the default output device.
+pub struct Output {
+    /// The stream, which plays while the output exists.
+    _stream: cpal::Stream,
+    /// The sounds being played, shared with the callback of the stream.
+    voices: Arc<Mutex<Vec<Voice>>>,
+    /// The sample rate of the device.
+    sample_rate: u32,
+}
+
+impl Output {
+    /// Opens the stream of the default output device.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Output, String>` - The output, or an error message if there is no usable device.
+    pub fn open() -> Result<Self, String> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or("no output device available")?;
+        let supported = device.default_output_config().map_err(|e| e.to_string())?;
+        let sample_format = supported.sample_format();
+        let config: cpal::StreamConfig = supported.into();
+
+        let voices = Arc::new(Mutex::new(Vec::new()));
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, voices.clone()),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, voices.clone()),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, voices.clone()),
+            format => Err(format!("unsupported sample format {}", format)),
+        }?;
+        stream.play().map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            _stream: stream,
+            voices,
+            sample_rate: config.sample_rate.0,
+        })
+    }
+
+    /// Returns the sample rate of the device.
+    ///
+    /// # Returns
+    ///
+    /// * The number of samples per second.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Starts playing a sound.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The mono samples of the sound at the sample rate of the device.
+    /// * `gain` - The volume of the sound.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn play(&self, samples: Arc<Vec<f32>>, gain: f32) {
+        let mut voices = self.voices.lock().unwrap();
+        if voices.len() >= MAX_VOICES {
+            voices.remove(0);
+        }
+        voices.push(Voice {
+            samples,
+            position: 0,
+            gain,
+        });
+    }
+}
+
+/// Builds the output stream for the sample format of the device.
+///
+/// # Arguments
+///
+/// * `device` - The output device.
+/// * `config` - The configuration of the stream.
+/// * `voices` - The sounds being played.
+///
+/// # Returns
+///
+/// * `Result<cpal::Stream, String>` - The stream, or an error message.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    voices: Arc<Mutex<Vec<Voice>>>,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let mut voices = voices.lock().unwrap();
+                for frame in data.chunks_mut(channels) {
+                    // Mix the sounds, the same value goes to all channels
+                    let value: f32 = voices
+                        .iter_mut()
+                        .filter_map(|voice| {
+                            let sample = voice.samples.get(voice.position)?;
+                            voice.position += 1;
+                            Some(sample * voice.gain)
+                        })
+                        .sum();
+                    let value = T::from_sample(value.clamp(-1.0, 1.0));
+                    for sample in frame.iter_mut() {
+                        *sample = value;
+                    }
+                }
+                voices.retain(|voice| voice.position < voice.samples.len());
+            },
+            |e| log::error!("An error occurred on the audio output stream: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,793 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the user configuration of the application, which is read from a TOML
+//! file at startup. Every section is optional, missing values fall back to the defaults.
+
+use crate::objects::Rarity;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The `Config` struct is the root of the configuration file.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Additional or overridden timing profiles of the maps.
+    pub timings: Vec<TimingProfile>,
+    /// Settings of the storm clock calibration.
+    pub clock: ClockConfig,
+    /// Placement and order of the overlay blocks.
+    pub layout: LayoutConfig,
+    /// Colors and font sizes of the overlay.
+    pub theme: ThemeConfig,
+    /// Placement of the overlay window, overridden by the placement saved in the edit mode.
+    pub window: WindowConfig,
+    /// Volume and sounds of the alerts.
+    pub audio: AudioConfig,
+    /// Capacity and animations of the event log.
+    pub log: LogConfig,
+    /// Display mode and columns of the time block.
+    pub time: TimeConfig,
+    /// Rules of the alerts about nearby players.
+    pub proximity: ProximityConfig,
+    /// Timeline of the evac ships and the log lines they are tracked by.
+    pub evac: EvacConfig,
+    /// Filters of the deaths shown in the event log.
+    pub kill_feed: KillFeedConfig,
+}
+
+impl Config {
+    /// Loads the configuration from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * Self - The loaded configuration, or the default one if the file doesn't exist or
+    ///   cannot be parsed, with the placement of the window saved in the edit mode.
+    pub fn load(path: &Path) -> Self {
+        let mut config = Self::load_file(path);
+        if let Some(window) = WindowConfig::load(&Self::window_path(path)) {
+            config.window = window;
+        }
+        config
+    }
+
+    /// Returns the path of the file with the placement of the window saved in the edit mode.
+    /// The placement is kept apart, so the configuration file written by the user is never
+    /// rewritten by the overlay.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * PathBuf - The path of `window.toml` next to the configuration file.
+    pub fn window_path(path: &Path) -> PathBuf {
+        path.with_file_name("window.toml")
+    }
+
+    /// Loads the configuration file without the saved placement of the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * Self - The loaded configuration, or the default one if the file doesn't exist or
+    ///   cannot be parsed.
+    fn load_file(path: &Path) -> Self {
+        // A missing configuration file is not an error, just use the defaults
+        if !path.exists() {
+            log::info!("Config file {:?} not found, using defaults", path);
+            return Self::default();
+        }
+
+        match std::fs::read_to_string(path) {
+            Ok(text) => match toml::from_str(&text) {
+                Ok(config) => {
+                    log::info!("Config loaded from {:?}", path);
+                    config
+                }
+                Err(e) => {
+                    log::error!("Cannot parse config file {:?}: {}", path, e);
+                    Self::default()
+                }
+            },
+            Err(e) => {
+                log::error!("Cannot read config file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// The `TimingProfile` struct describes the day cycle of one or several maps.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TimingProfile {
+    /// The unique name of the profile. A profile named as a built-in one replaces it.
+    pub name: String,
+    /// The names of the maps in the game logs (e.g. `MAP01`) which use this profile.
+    #[serde(default)]
+    pub maps: Vec<String>,
+    /// The name of the phase that begins at the moment the server is created.
+    /// The first phase is used if omitted.
+    #[serde(default)]
+    pub start_phase: Option<String>,
+    /// The phases of the cycle in their order.
+    pub phases: Vec<PhaseConfig>,
+    /// The changelist of the latest game build the profile was verified against.
+    #[serde(default)]
+    pub verified_build: Option<u64>,
+}
+
+/// The `PhaseConfig` struct describes a single phase of the day cycle.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PhaseConfig {
+    /// The name of the phase.
+    pub name: String,
+    /// The duration of the phase in seconds.
+    pub duration: u64,
+    /// The color used to display the phase.
+    pub color: Color,
+}
+
+/// The `ClockConfig` struct contains settings of the storm clock calibration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// Log lines which mark the beginning of a phase of the day cycle.
+    pub anchors: Vec<ClockAnchor>,
+}
+
+/// The `ClockAnchor` struct describes a log line which marks the beginning of a phase.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClockAnchor {
+    /// The category of the log line, e.g. `LogYWeather`.
+    pub category: String,
+    /// The text that the log line contains.
+    pub contains: String,
+    /// The name of the phase which begins, as named in the timing profile of the map.
+    pub phase: String,
+}
+
+/// The `EvacConfig` struct contains the timeline of the evac ships and the log lines they are
+/// tracked by.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EvacConfig {
+    /// The phases of an evac ship from the call to the departure, in their order.
+    pub phases: Vec<EvacPhaseConfig>,
+    /// The log line which marks the call of an evac ship by the player.
+    pub own_call: Option<LogLine>,
+    /// The longest time between the call of the player and the evac ship in seconds.
+    pub own_call_window: u64,
+}
+
+impl Default for EvacConfig {
+    /// Provides the timeline of 86 seconds which is known from the game.
+    fn default() -> Self {
+        Self {
+            phases: vec![
+                EvacPhaseConfig::new("called", 10, false),
+                EvacPhaseConfig::new("arriving", 37, false),
+                EvacPhaseConfig::new("landed", 29, false),
+                EvacPhaseConfig::new("flying", 10, true),
+            ],
+            own_call: None,
+            own_call_window: 10,
+        }
+    }
+}
+
+/// The `EvacPhaseConfig` struct describes a single phase of an evac ship.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EvacPhaseConfig {
+    /// The name of the phase.
+    pub name: String,
+    /// The duration of the phase in seconds.
+    pub duration: u64,
+    /// The text of a `LogYActivities` line which marks the beginning of the phase.
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// Whether the evac ship cannot be boarded anymore in this phase.
+    #[serde(default)]
+    pub inactive: bool,
+}
+
+impl EvacPhaseConfig {
+    /// Creates a phase which is recognized by its time only.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the phase.
+    /// * `duration` - The duration of the phase in seconds.
+    /// * `inactive` - Whether the evac ship cannot be boarded anymore.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `EvacPhaseConfig`.
+    pub fn new(name: &str, duration: u64, inactive: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            duration,
+            contains: None,
+            inactive,
+        }
+    }
+}
+
+/// The `LogLine` struct describes a log line by its category and a part of its text.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogLine {
+    /// The category of the log line, e.g. `LogYActivities`.
+    pub category: String,
+    /// The text that the log line contains.
+    pub contains: String,
+}
+
+/// The `LayoutConfig` struct contains the placement and order of the overlay blocks.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// The corner or side of the screen the overlay is attached to.
+    pub anchor: Anchor,
+    /// The offset of the overlay from the anchor in points, `[x, y]`.
+    pub offset: [f32; 2],
+    /// The vertical space between the blocks in points.
+    pub block_spacing: f32,
+    /// The names of the displayed blocks in their order, omitted blocks are hidden.
+    pub blocks: Vec<String>,
+}
+
+impl Default for LayoutConfig {
+    /// Provides the layout of the overlay in the top right corner of the screen.
+    fn default() -> Self {
+        Self {
+            anchor: Anchor::RightTop,
+            offset: [0.0, 0.0],
+            block_spacing: 0.0,
+            blocks: vec![
+                "server".to_string(),
+                "time".to_string(),
+                "summary".to_string(),
+                "recap".to_string(),
+                "log".to_string(),
+            ],
+        }
+    }
+}
+
+/// The `Anchor` enum is the corner or side of the screen the overlay is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    LeftTop,
+    CenterTop,
+    #[default]
+    RightTop,
+    LeftCenter,
+    Center,
+    RightCenter,
+    LeftBottom,
+    CenterBottom,
+    RightBottom,
+}
+
+impl From<Anchor> for egui::Align2 {
+    /// Converts the anchor into the alignment of `egui`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The anchor to convert.
+    ///
+    /// # Returns
+    ///
+    /// * Self - The alignment corresponding to the anchor.
+    fn from(value: Anchor) -> Self {
+        match value {
+            Anchor::LeftTop => egui::Align2::LEFT_TOP,
+            Anchor::CenterTop => egui::Align2::CENTER_TOP,
+            Anchor::RightTop => egui::Align2::RIGHT_TOP,
+            Anchor::LeftCenter => egui::Align2::LEFT_CENTER,
+            Anchor::Center => egui::Align2::CENTER_CENTER,
+            Anchor::RightCenter => egui::Align2::RIGHT_CENTER,
+            Anchor::LeftBottom => egui::Align2::LEFT_BOTTOM,
+            Anchor::CenterBottom => egui::Align2::CENTER_BOTTOM,
+            Anchor::RightBottom => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+}
+
+/// The `ThemeConfig` struct contains the colors and font sizes of the overlay.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// The preset the theme is based on.
+    pub preset: Preset,
+    /// Colors overriding the preset by their names, e.g. `server` or `legendary`.
+    pub colors: BTreeMap<String, Color>,
+    /// Font sizes overriding the preset by their names, e.g. `time`.
+    pub font_sizes: BTreeMap<String, f32>,
+    /// Colors of the phases of the day cycle in their order, overriding the timing profiles.
+    pub phase_colors: Vec<Color>,
+    /// The horizontal space between the labels of a block in points.
+    pub spacing: Option<f32>,
+}
+
+/// The `Preset` enum is a predefined set of colors and font sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// The original colors of the overlay.
+    #[default]
+    Default,
+    /// Colors which are distinguishable with any type of color blindness.
+    Colorblind,
+    /// Bright colors and larger small text.
+    HighContrast,
+}
+
+/// The `WindowConfig` struct contains the placement of the overlay window.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// The monitor the window is placed on, the primary one if omitted.
+    pub monitor: Option<MonitorChoice>,
+    /// The corner or side of the monitor the window is attached to.
+    pub anchor: Anchor,
+    /// The offset of the window from the anchor, `[x, y]`. It is scaled like the window size.
+    pub offset: [i32; 2],
+    /// The position of the window relative to the top left corner of the monitor in physical
+    /// pixels, `[x, y]`. It is saved by the edit mode and takes precedence over the anchor.
+    pub position: Option<[i32; 2]>,
+    /// The size of the window, `[width, height]`. It is scaled like the fonts.
+    pub size: Option<[u32; 2]>,
+    /// The scale of the fonts and the window. If omitted, it is chosen automatically from
+    /// the resolution of the monitor, so the overlay looks the same as on a 1080p monitor.
+    pub scale: Option<f32>,
+}
+
+impl WindowConfig {
+    /// Loads the placement of the window saved in the edit mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the window placement file.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the saved placement, or None if there is none or it cannot be parsed.
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        match toml::from_str(&text) {
+            Ok(window) => {
+                log::info!("Window placement loaded from {:?}", path);
+                Some(window)
+            }
+            Err(e) => {
+                log::error!("Cannot parse window placement file {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Saves the placement of the window. A file which exists but cannot be read or parsed is
+    /// left as it is, so a broken file is never replaced without the user noticing.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the window placement file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error message if the placement cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if path.exists() {
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            toml::from_str::<Self>(&text)
+                .map_err(|e| format!("{:?} is broken and was not overwritten: {}", path, e))?;
+        }
+
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())?;
+        log::info!("Window placement saved to {:?}", path);
+        Ok(())
+    }
+}
+
+impl Default for WindowConfig {
+    /// Provides the placement of the window in the top right corner of the primary monitor.
+    fn default() -> Self {
+        Self {
+            monitor: None,
+            anchor: Anchor::RightTop,
+            offset: [-30, 30],
+            position: None,
+            size: None,
+            scale: None,
+        }
+    }
+}
+
+/// The `MonitorChoice` enum selects a monitor by its index or its name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MonitorChoice {
+    /// The index of the monitor in the list of the system, starting from 0.
+    Index(usize),
+    /// The name of the monitor, e.g. `\\.\DISPLAY2`.
+    Name(String),
+}
+
+/// The `LogConfig` struct contains the capacity and the animations of the event log.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// The maximum number of events in the log, the oldest ones are removed first.
+    pub capacity: usize,
+    /// The duration of the fade-out before an event expires in seconds, 0 to disable it.
+    pub fade: f32,
+    /// The duration of the slide-in of a new event in seconds, 0 to disable it.
+    pub slide: f32,
+    /// Whether repeated identical events are shown once with a counter.
+    pub group: bool,
+}
+
+impl Default for LogConfig {
+    /// Provides a short log with quick animations.
+    fn default() -> Self {
+        Self {
+            capacity: 8,
+            fade: 1.0,
+            slide: 0.3,
+            group: true,
+        }
+    }
+}
+
+/// The `KillFeedConfig` struct contains the filters of the deaths shown in the event log.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KillFeedConfig {
+    /// The categories of the causers whose kills are shown.
+    pub causers: Vec<KillCauser>,
+    /// The lowest rarity of the weapon of the shown kills, kills without a weapon are shown.
+    pub min_rarity: Option<Rarity>,
+    /// Whether only the deaths of the player are shown.
+    pub only_mine: bool,
+    /// The text that the log lines of the deaths and escapes of the player contain, e.g. the
+    /// player name. Without it all deaths and escapes are taken as the player's.
+    pub own_death: Option<String>,
+    /// Whether the kills of AI creatures are collapsed into one summary line.
+    pub collapse_ai: bool,
+}
+
+impl Default for KillFeedConfig {
+    /// Provides the feed with all deaths.
+    fn default() -> Self {
+        Self {
+            causers: vec![
+                KillCauser::Player,
+                KillCauser::Creature,
+                KillCauser::Environment,
+                KillCauser::Own,
+            ],
+            min_rarity: None,
+            only_mine: false,
+            own_death: None,
+            collapse_ai: false,
+        }
+    }
+}
+
+/// The `KillCauser` enum is the category of what caused a death.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillCauser {
+    /// Another player.
+    Player,
+    /// An AI creature.
+    Creature,
+    /// The environment, e.g. a fall or an unknown causer.
+    Environment,
+    /// The player themselves.
+    #[serde(rename = "self")]
+    Own,
+}
+
+/// The `TimeConfig` struct contains the display mode, the columns and the alerts of the time
+/// block.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimeConfig {
+    /// How the countdowns are displayed.
+    pub mode: TimeMode,
+    /// The hidden columns: the names of the phases, `server` and `accuracy`.
+    pub hidden: Vec<String>,
+    /// The alerts before the phases begin or the server shuts down.
+    pub alerts: Vec<CountdownAlert>,
+}
+
+impl Default for TimeConfig {
+    /// Provides the compact countdowns with alerts before the night, the storm and the server
+    /// shutdown.
+    fn default() -> Self {
+        Self {
+            mode: TimeMode::default(),
+            hidden: Vec::new(),
+            alerts: vec![
+                CountdownAlert::new("night_soon", "night", 60),
+                CountdownAlert::new("storm_soon", "storm", 30),
+                CountdownAlert::new("server_shutdown", "server", 15 * 60),
+            ],
+        }
+    }
+}
+
+impl TimeConfig {
+    /// Checks whether a column is shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The name of the column, case-insensitive.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - False if the column is hidden.
+    pub fn shows(&self, column: &str) -> bool {
+        !self
+            .hidden
+            .iter()
+            .any(|hidden| hidden.eq_ignore_ascii_case(column))
+    }
+}
+
+/// The `CountdownAlert` struct describes an alert before a phase begins or the server shuts down.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CountdownAlert {
+    /// The name of the alert, which is also the name of its sound in the audio section.
+    pub name: String,
+    /// The name of the phase as in the timing profile, or `server` for the server shutdown.
+    pub target: String,
+    /// The time before the target in seconds.
+    pub before: u64,
+    /// Whether the alert is played at all.
+    pub enabled: bool,
+    /// Whether a message flashes on the screen.
+    pub flash: bool,
+}
+
+impl CountdownAlert {
+    /// Creates an enabled alert with the flashing message.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the alert and its sound.
+    /// * `target` - The name of the phase or `server`.
+    /// * `before` - The time before the target in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `CountdownAlert`.
+    pub fn new(name: &str, target: &str, before: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            target: target.to_string(),
+            before,
+            enabled: true,
+            flash: true,
+        }
+    }
+}
+
+impl Default for CountdownAlert {
+    /// Provides an alert a minute before the storm, which is completed by the configuration.
+    fn default() -> Self {
+        Self::new("storm_soon", "storm", 60)
+    }
+}
+
+/// The `TimeMode` enum is the way the time block displays the countdowns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeMode {
+    /// The countdowns separated by slashes.
+    #[default]
+    Compact,
+    /// The countdowns with the names of the phases.
+    Labelled,
+    /// A bar of the day cycle with the current phase, the next storm and the server end.
+    Bar,
+}
+
+/// The `ProximityConfig` struct contains the rules of the alerts about nearby players.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProximityConfig {
+    /// Whether the alerts are played at all.
+    pub enabled: bool,
+    /// The number of nearby players outside the party which is the first contact.
+    pub threshold: usize,
+    /// The number of additional nearby players after the last alert which is another contact.
+    pub step: usize,
+    /// The shortest interval between two alerts in seconds.
+    pub cooldown: u64,
+    /// Whether the number of nearby players flashes on the screen with the alert.
+    pub flash: bool,
+}
+
+impl Default for ProximityConfig {
+    /// Provides an alert for every player coming near, at most every 5 seconds.
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1,
+            step: 1,
+            cooldown: 5,
+            flash: true,
+        }
+    }
+}
+
+/// The `AudioConfig` struct contains the volume and the sounds of the alerts.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// The master volume from 0 to 1.
+    pub volume: f32,
+    /// Whether all sounds are muted at startup.
+    pub muted: bool,
+    /// The shortest interval between two sounds of the same alert in milliseconds.
+    pub min_interval: u64,
+    /// The sounds of the alerts by their names, overriding the built-in tones.
+    pub sounds: BTreeMap<String, SoundConfig>,
+}
+
+impl Default for AudioConfig {
+    /// Provides the half volume and the built-in tones.
+    fn default() -> Self {
+        Self {
+            volume: 0.5,
+            muted: false,
+            min_interval: 1000,
+            sounds: BTreeMap::new(),
+        }
+    }
+}
+
+/// The `SoundConfig` enum describes the sound of an alert.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SoundConfig {
+    /// A sine tone, e.g. `{ frequency = 2000, duration = 250 }` with the duration in milliseconds.
+    Tone { frequency: f32, duration: u64 },
+    /// A WAV or OGG file, e.g. `{ file = "sounds/alert.ogg" }`. A relative path starts from
+    /// the directory of the configuration file.
+    File { file: std::path::PathBuf },
+}
+
+/// The `Color` struct is an RGB(A) color written as a `#rrggbb` or `#rrggbbaa` string.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(pub egui::Color32);
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    /// Parses a color from a hex string.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The string in `#rrggbb` or `#rrggbbaa` format.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Color, String>` - The parsed color, or an error message.
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let hex = value.trim_start_matches('#');
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .ok_or_else(|| format!("invalid color: {}", value))
+        };
+
+        match hex.len() {
+            6 => Ok(Color(egui::Color32::from_rgb(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+            ))),
+            8 => Ok(Color(egui::Color32::from_rgba_unmultiplied(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            ))),
+            _ => Err(format!("invalid color: {}", value)),
+        }
+    }
+}
+
+impl From<Color> for String {
+    /// Formats a color as a hex string.
+    fn from(value: Color) -> Self {
+        let [r, g, b, a] = value.0.to_srgba_unmultiplied();
+        if a == u8::MAX {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+}
+
+impl From<Color> for egui::Color32 {
+    /// Converts a configured color to the `egui` one.
+    fn from(value: Color) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a path in a fresh temporary directory of the test.
+    fn temp_path(test: &str, name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cycle_log_parser_{}", test));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn saved_placement_overrides_the_window_section() {
+        let path = temp_path("placement", "config.toml");
+        let text = "# Placed by hand\n[window]\noffset = [1, 2]\n";
+        std::fs::write(&path, text).unwrap();
+
+        let mut window = Config::load(&path).window;
+        assert_eq!(window.offset, [1, 2]);
+        window.position = Some([10, 20]);
+        window.save(&Config::window_path(&path)).unwrap();
+
+        assert_eq!(Config::load(&path).window.position, Some([10, 20]));
+        // The configuration written by the user is left as it is
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+    }
+
+    #[test]
+    fn broken_placement_is_not_overwritten() {
+        let path = temp_path("broken", "window.toml");
+        std::fs::write(&path, "position = [").unwrap();
+
+        assert!(WindowConfig::load(&path).is_none());
+        assert!(WindowConfig::default().save(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "position = [");
+    }
+}
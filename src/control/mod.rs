@@ -0,0 +1,260 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains control commands, which adjust the running application without
+//! a console. The commands are appended as lines to a control file, which the running
+//! application follows in the same way as the game log.
+
+use crate::overlay::events;
+use crate::state::StateHolder;
+
+use tokio::io::AsyncBufReadExt;
+
+use std::io::Write;
+use std::sync;
+
+/// Enum representing a control command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Shifts the storm clock of the current game by the given number of seconds.
+    Clock(i64),
+    /// Drops the manual corrections of the storm clock of the current game.
+    ClockReset,
+    /// Turns the edit mode of the overlay on or off, or toggles it if None.
+    Edit(Option<bool>),
+    /// Sets the volume of the alerts in percent.
+    Volume(u8),
+    /// Mutes or unmutes the alerts, or toggles the mute if None.
+    Mute(Option<bool>),
+    /// Shows or hides the history of the matches, or toggles it if None, and moves back (positive)
+    /// or forward (negative) by the given number of matches.
+    History(Option<bool>, isize),
+    /// Closes the recap of the death of the player.
+    Dismiss,
+}
+
+impl std::str::FromStr for Command {
+    type Err = String;
+
+    /// Parses a command from a line of the control file.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The line, e.g. `clock +5`, `clock reset`, `edit on` or `volume 50`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Command, String>` - The parsed command, or an error message.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let name = words.next().unwrap_or_default().to_lowercase();
+        let argument = words.next();
+
+        match (name.as_str(), argument) {
+            ("clock", Some("reset")) => Ok(Command::ClockReset),
+            ("clock", Some(seconds)) => seconds
+                .trim_start_matches('+')
+                .parse::<i64>()
+                .map(Command::Clock)
+                .map_err(|_| format!("invalid number of seconds: {}", seconds)),
+            ("edit", None) => Ok(Command::Edit(None)),
+            ("edit", Some("on")) => Ok(Command::Edit(Some(true))),
+            ("edit", Some("off")) => Ok(Command::Edit(Some(false))),
+            ("volume", Some(percent)) => percent
+                .trim_end_matches('%')
+                .parse::<u8>()
+                .ok()
+                .filter(|percent| *percent <= 100)
+                .map(Command::Volume)
+                .ok_or_else(|| format!("invalid volume: {}", percent)),
+            ("mute", None) => Ok(Command::Mute(None)),
+            ("mute", Some("on")) => Ok(Command::Mute(Some(true))),
+            ("mute", Some("off")) => Ok(Command::Mute(Some(false))),
+            ("history", None) => Ok(Command::History(None, 0)),
+            ("history", Some("on")) => Ok(Command::History(Some(true), 0)),
+            ("history", Some("off")) => Ok(Command::History(Some(false), 0)),
+            ("history", Some("prev")) => Ok(Command::History(Some(true), 1)),
+            ("history", Some("next")) => Ok(Command::History(Some(true), -1)),
+            ("dismiss", None) => Ok(Command::Dismiss),
+            _ => Err(format!("unknown command: {}", s)),
+        }
+    }
+}
+
+/// Represents a listener for the control file.
+pub struct Listener {
+    /// Shared global state holder.
+    state: sync::Arc<StateHolder>,
+    /// The audio service which plays the alerts.
+    audio: crate::audio::Audio,
+}
+
+impl Listener {
+    /// Creates a new listener with given state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - A shared reference to an instance of `StateHolder`.
+    /// * `audio` - The audio service which plays the alerts.
+    ///
+    /// # Return
+    ///
+    /// This function will return an instance of `Listener`.
+    pub fn new(state: sync::Arc<StateHolder>, audio: crate::audio::Audio) -> Self {
+        Self { state, audio }
+    }
+
+    /// Follows the control file and executes the commands appended to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path of the control file.
+    /// * `event_loop_proxy` - A shared reference to an instance of `EventLoopProxy` which is
+    ///   responsible for sending events.
+    ///
+    /// # Return
+    ///
+    /// * None
+    pub async fn process_control_file(
+        &mut self,
+        file_path: std::path::PathBuf,
+        event_loop_proxy: sync::Arc<sync::Mutex<winit::event_loop::EventLoopProxy<events::Action>>>,
+    ) {
+        // Commands left from the previous run must not be executed again
+        if let Err(e) = tokio::fs::write(&file_path, "").await {
+            log::error!("Cannot create control file {:?}: {}", file_path, e);
+            return;
+        }
+
+        log::info!("Processing control file {:?} started...", file_path);
+
+        match tokio::fs::File::open(file_path).await {
+            Ok(file) => {
+                let mut reader = tokio::io::BufReader::new(file).lines();
+
+                loop {
+                    // Read lines from the file and execute them
+                    match reader.next_line().await {
+                        Ok(Some(line)) if line.trim().is_empty() => (),
+                        Ok(Some(line)) => match line.parse::<Command>() {
+                            Ok(command) => self.execute(command, event_loop_proxy.clone()),
+                            Err(e) => log::error!("Cannot execute control command: {}", e),
+                        },
+                        Ok(None) => {
+                            // If there is no more line to read, pause for a moment
+                            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                        }
+                        Err(e) => log::error!("Error reading line from control file: {}", e),
+                    }
+                }
+            }
+            Err(e) => log::error!("Control file not found: {}", e),
+        }
+    }
+
+    /// Executes a command.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute.
+    /// * `event_loop_proxy` - A shared reference to an instance of `EventLoopProxy` which is
+    ///   responsible for sending events.
+    ///
+    /// # Return
+    ///
+    /// * None
+    fn execute(
+        &mut self,
+        command: Command,
+        event_loop_proxy: sync::Arc<sync::Mutex<winit::event_loop::EventLoopProxy<events::Action>>>,
+    ) {
+        log::info!("Control command: {:?}", command);
+
+        match command {
+            Command::Clock(_) | Command::ClockReset => {
+                if !self.state.is_in_game() {
+                    log::error!("Cannot correct the storm clock outside of a game");
+                    return;
+                }
+                if let Some(game) = self.state.get_games().lock().unwrap().front_mut() {
+                    // Correct the storm clock of the current game
+                    if let Command::Clock(seconds) = command {
+                        game.clock.correct(seconds);
+                    } else {
+                        game.clock.reset_correction();
+                    }
+
+                    // Send an update to the game state in `Overlay`
+                    let sender = event_loop_proxy.lock().unwrap();
+                    sender
+                        .send_event(events::Action::UpdateState(events::UpdateState::new(Some(
+                            game.clone(),
+                        ))))
+                        .unwrap();
+                }
+            }
+            Command::Edit(enabled) => {
+                // The window is owned by `Overlay`, so the request is sent to it
+                let sender = event_loop_proxy.lock().unwrap();
+                sender
+                    .send_event(events::Action::SetEditMode(events::SetEditMode::new(
+                        enabled,
+                    )))
+                    .unwrap();
+            }
+            Command::Volume(percent) => self.audio.set_volume(percent as f32 / 100.0),
+            Command::Mute(muted) => self.audio.set_muted(muted),
+            Command::History(visible, step) => {
+                // The history is kept by `Overlay` as well
+                let sender = event_loop_proxy.lock().unwrap();
+                sender
+                    .send_event(events::Action::BrowseHistory(events::BrowseHistory::new(
+                        visible, step,
+                    )))
+                    .unwrap();
+            }
+            Command::Dismiss => {
+                let sender = event_loop_proxy.lock().unwrap();
+                sender.send_event(events::Action::DismissRecap).unwrap();
+            }
+        }
+    }
+}
+
+/// Appends a command to the control file of the running application.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the control file.
+/// * `command` - The command to append.
+///
+/// # Return
+///
+/// * `Result<(), String>` - An error message if the command is invalid or cannot be written.
+pub fn send(file_path: &std::path::Path, command: &str) -> Result<(), String> {
+    // Validate the command before sending it
+    command.parse::<Command>()?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(file_path)
+        .map_err(|e| format!("the application is not running ({})", e))?;
+    writeln!(file, "{}", command).map_err(|e| e.to_string())
+}
@@ -22,6 +22,9 @@
 //! Main module of the application. It creates and runs the graphical interface,
 //! and also starts parsers in a parallel asynchronous thread.
 
+mod audio;
+mod config;
+mod control;
 mod objects;
 mod overlay;
 mod parsers;
@@ -50,29 +53,104 @@ async fn main() {
     // Initializes the logger
     env_logger::init();
 
-    // Gets the path to the game log
-    let log_path = get_log_path();
-    info!("Game logs path: {:?}", log_path);
+    // Parses the command-line arguments
+    let args: Vec<String> = env::args().collect();
 
-    // Checks if the game log exists
-    if !log_path.exists() {
-        error!("Game log doesn't exist!");
-        std::process::exit(-1);
+    // Sends a control command to the running application if requested
+    if args.len() > 2 && args[1] == "--command" {
+        let command = args[2..].join(" ");
+        if let Err(e) = control::send(&get_control_path(), &command) {
+            error!("Cannot send control command: {}", e);
+            std::process::exit(-1);
+        }
+        return;
     }
 
-    info!("Starting log parsers...");
+    // Loads the user configuration, whose path can be overridden by the environment
+    let config_path = env::var_os("PROSPECT_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(get_config_path);
+    let config = config::Config::load(&config_path);
+    // Registers the timing profiles from the configuration
+    objects::Timings::register(&config.timings);
 
-    // Parses the command-line arguments, or uses default values
-    let args: Vec<String> = env::args().collect();
+    // Starts in the edit mode if requested, the flags can be anywhere in the arguments
+    let edit_mode = args.iter().any(|arg| arg == "--edit");
+    // Renders on the CPU if requested, e.g. when the GPU driver misbehaves
+    let software = args.iter().any(|arg| arg == "--software");
+    let mut args: Vec<String> = args
+        .into_iter()
+        .filter(|arg| arg != "--edit" && arg != "--software")
+        .collect();
+    // Renders a screenshot instead of running the overlay if requested
+    let screenshot = take_option(&mut args, "--screenshot");
+    let screenshot_time = take_option(&mut args, "--at");
+    let screenshot_scene = take_option(&mut args, "--scene");
+
+    // Uses the window size from the command-line arguments, the saved size, or default values
     let (width, height) = if args.len() == 3 {
         (
             args[1].parse::<f32>().unwrap(),
             args[2].parse::<f32>().unwrap(),
         )
+    } else if let Some([width, height]) = config.window.size {
+        (width as f32, height as f32)
     } else {
         (800.0, 600.0)
     };
 
+    // Renders a scene or the sample match into the image, which doesn't need the game or a GPU
+    if let Some(path) = screenshot {
+        let scene = match screenshot_scene.map(|scene| overlay::Scene::load(&PathBuf::from(scene)))
+        {
+            Some(Ok(scene)) => scene,
+            Some(Err(e)) => {
+                error!("Invalid scene of the screenshot: {}", e);
+                std::process::exit(-1);
+            }
+            None => overlay::Scene::sample(),
+        };
+        let now = match screenshot_time.map(|time| chrono::DateTime::parse_from_rfc3339(&time)) {
+            Some(Ok(time)) => time.with_timezone(&chrono::Utc),
+            Some(Err(e)) => {
+                error!("Invalid time of the screenshot: {}", e);
+                std::process::exit(-1);
+            }
+            None => scene.at.unwrap_or_else(chrono::Utc::now),
+        };
+        let (state, actions) = scene.build(now);
+        let overlay = Overlay::new(
+            width,
+            height,
+            state,
+            &config,
+            config_path,
+            false,
+            software,
+            audio::Audio::silent(),
+        );
+        if let Err(e) = overlay.screenshot(actions, now, std::path::Path::new(&path)) {
+            error!("Cannot write the screenshot: {}", e);
+            std::process::exit(-1);
+        }
+        info!("Screenshot is written to {}", path);
+        return;
+    }
+
+    // Gets the path to the game log, which can be overridden by the environment
+    let log_path = env::var_os("PROSPECT_LOG")
+        .map(PathBuf::from)
+        .unwrap_or_else(get_log_path);
+    info!("Game logs path: {:?}", log_path);
+
+    // Checks if the game log exists
+    if !log_path.exists() {
+        error!("Game log doesn't exist!");
+        std::process::exit(-1);
+    }
+
+    info!("Starting log parsers...");
+
     // Creates the shared application state
     let state = std::sync::Arc::new(state::StateHolder::new());
 
@@ -81,8 +159,19 @@ async fn main() {
         winit::event_loop::EventLoopBuilder::<overlay::events::Action>::with_user_event().build();
     let event_loop_proxy = std::sync::Arc::new(std::sync::Mutex::new(event_loop.create_proxy()));
 
+    // Starts the audio service, the sound files are next to the configuration file
+    let audio = audio::Audio::start(
+        &config.audio,
+        config_path.parent().unwrap_or(std::path::Path::new(".")),
+    );
+
     // Initializes the parser listener
-    let mut listener = parsers::Listener::new(state.clone());
+    let mut listener = parsers::Listener::new(state.clone(), &config, audio.clone());
+
+    // Initializes the control file listener
+    let mut control_listener = control::Listener::new(state.clone(), audio.clone());
+    let control_path = get_control_path();
+    let control_proxy = event_loop_proxy.clone();
 
     // Creates a new runtime for the parser
     let parser_runtime = tokio::runtime::Runtime::new().unwrap();
@@ -91,12 +180,50 @@ async fn main() {
     let parser_handle = parser_runtime
         .spawn(async move { listener.process_log_file(log_path, event_loop_proxy).await });
 
+    // Starts following the control file in the runtime
+    let control_handle = parser_runtime.spawn(async move {
+        control_listener
+            .process_control_file(control_path, control_proxy)
+            .await
+    });
+
     // Initializes and runs the graphical interface
-    let overlay = Overlay::new(width, height, state);
+    let overlay = Overlay::new(
+        width,
+        height,
+        state,
+        &config,
+        config_path,
+        edit_mode,
+        software,
+        audio,
+    );
     overlay.run(event_loop).await;
 
-    // Aborts the parser thread when the GUI closes
+    // Aborts the parser threads when the GUI closes
     parser_handle.abort();
+    control_handle.abort();
+}
+
+/// This function removes an option with its value from the command-line arguments.
+///
+/// # Arguments
+///
+/// * `args` - The command-line arguments.
+/// * `name` - The name of the option, e.g. `--screenshot`.
+///
+/// # Returns
+///
+/// * The value of the option, or None if it is not given.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == name)?;
+    if index + 1 >= args.len() {
+        args.remove(index);
+        return None;
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Some(value)
 }
 
 /// This function retrieves the path to the game log file.
@@ -111,6 +238,7 @@ async fn main() {
 /// # Returns
 ///
 /// * A `PathBuf` that represents the absolute path to the game log file.
+#[cfg(windows)]
 fn get_log_path() -> PathBuf {
     // Gets the path to the local application data directory
     let mut prospect = env::var("LOCALAPPDATA").expect("Could not find LOCALAPPDATA");
@@ -121,3 +249,64 @@ fn get_log_path() -> PathBuf {
     // Returns the full path to the game log file
     PathBuf::from(prospect)
 }
+
+/// This function retrieves the path to the game log file.
+///
+/// The game runs on Linux through Proton, which keeps the Windows profile of the game
+/// in the Steam library of the user.
+///
+/// # Arguments
+///
+/// * None
+///
+/// # Returns
+///
+/// * A `PathBuf` that represents the absolute path to the game log file.
+#[cfg(not(windows))]
+fn get_log_path() -> PathBuf {
+    // Gets the home directory of the user
+    let home = env::var("HOME").expect("Could not find HOME");
+
+    // Appends the path to the game log file in the Proton prefix of the game
+    PathBuf::from(home).join(
+        ".steam/steam/steamapps/compatdata/868270/pfx/drive_c/users/steamuser/AppData/Local/Prospect/Saved/Logs/Prospect.log",
+    )
+}
+
+/// This function retrieves the path to the configuration file.
+///
+/// The configuration file is expected to be located next to the executable.
+///
+/// # Arguments
+///
+/// * None
+///
+/// # Returns
+///
+/// * A `PathBuf` that represents the path to the configuration file.
+fn get_config_path() -> PathBuf {
+    // Gets the path to the executable, or the working directory if it's unknown
+    let exe = env::current_exe().unwrap_or_default();
+
+    // Returns the path to the configuration file next to the executable
+    exe.with_file_name("config.toml")
+}
+
+/// This function retrieves the path to the control file.
+///
+/// The control file is located next to the executable.
+///
+/// # Arguments
+///
+/// * None
+///
+/// # Returns
+///
+/// * A `PathBuf` that represents the path to the control file.
+fn get_control_path() -> PathBuf {
+    // Gets the path to the executable, or the working directory if it's unknown
+    let exe = env::current_exe().unwrap_or_default();
+
+    // Returns the path to the control file next to the executable
+    exe.with_file_name("control.txt")
+}
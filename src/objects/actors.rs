@@ -25,6 +25,9 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
+/// The changelist of the latest game build the actors were verified against.
+pub const VERIFIED_BUILD: super::build::VerifiedBuild = super::build::BUILT_IN_BUILD;
+
 /// Struct representing an actor in the game.
 #[derive(Debug, Clone)]
 pub struct Actor {
@@ -0,0 +1,181 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the build of the game, which is written in the header of the game log,
+//! and the compatibility check of the built-in data against it.
+
+use lazy_static::lazy_static;
+
+use std::fmt;
+
+/// The changelist of the game build some data was verified against. The data is reported as
+/// outdated once the game is updated past that build, so it can be checked again; `None` means
+/// the data is not bound to a build and never reported, e.g. a configured timing profile without
+/// `verified_build`.
+pub type VerifiedBuild = Option<u64>;
+
+/// The changelist of the game build the built-in catalogs and timing profiles were verified
+/// against, raised whenever they are checked against a newer build.
+pub const BUILT_IN_BUILD: VerifiedBuild = Some(2_400_000);
+
+/// Struct representing a build of the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameBuild {
+    /// The version of the build as written in the game log.
+    pub version: String,
+    /// The changelist number of the build, which grows with every build.
+    pub changelist: u64,
+}
+
+impl GameBuild {
+    /// Parses the build from a line of the game log header, such as
+    /// `LogInit: Build: ++Prospect+Release-CL-123456` or
+    /// `LogInit: Engine Version: 4.26.2-123456+++Prospect+Release`.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_` - The type of the log line.
+    /// * `text` - The text of the log line.
+    ///
+    /// # Return
+    ///
+    /// This function will return `Some(GameBuild)` if the line contains a build with a known
+    /// changelist, otherwise `None`.
+    pub fn from_log(type_: &str, text: &str) -> Option<Self> {
+        if type_ != "LogInit" {
+            return None;
+        }
+
+        let version = text
+            .strip_prefix("Build: ")
+            .or_else(|| text.strip_prefix("Engine Version: "))?
+            .trim();
+
+        let changelist = CHANGELIST
+            .captures(version)
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+            .and_then(|changelist| changelist.as_str().parse::<u64>().ok())
+            // Changelist 0 means a local build, which cannot be compared
+            .filter(|changelist| *changelist > 0)?;
+
+        Some(Self {
+            version: version.to_string(),
+            changelist,
+        })
+    }
+
+    /// Checks whether the build is newer than the build the data was verified against.
+    ///
+    /// # Arguments
+    ///
+    /// * `verified` - The changelist of the build the data was verified against, if any.
+    ///
+    /// # Return
+    ///
+    /// This function will return `true` if the data was verified against an older build.
+    pub fn is_newer_than(&self, verified: VerifiedBuild) -> bool {
+        verified.is_some_and(|verified| self.changelist > verified)
+    }
+
+    /// Returns the names of the data which were verified against older builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `timings` - The timing profile of the current map.
+    ///
+    /// # Return
+    ///
+    /// This function will return the names of the outdated data, empty if all data is up to date.
+    pub fn outdated_data(&self, timings: &super::Timings) -> Vec<String> {
+        let mut outdated = Vec::new();
+
+        if self.is_newer_than(super::actors::VERIFIED_BUILD) {
+            outdated.push("actors".to_string());
+        }
+        if self.is_newer_than(super::weapons::VERIFIED_BUILD) {
+            outdated.push("weapons".to_string());
+        }
+        if self.is_newer_than(timings.verified_build) {
+            outdated.push(format!("timings ({})", timings.name));
+        }
+
+        outdated
+    }
+}
+
+impl fmt::Display for GameBuild {
+    /// Formats the build as its changelist.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CL-{}", self.changelist)
+    }
+}
+
+lazy_static! {
+    /// Pattern of the changelist in the build version, either `CL-123456` or `4.26.2-123456`.
+    static ref CHANGELIST: regex::Regex =
+        regex::Regex::new(r"CL-(\d+)|^\d+\.\d+\.\d+-(\d+)").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(changelist: u64) -> GameBuild {
+        GameBuild {
+            version: format!("++Prospect+Release-CL-{}", changelist),
+            changelist,
+        }
+    }
+
+    #[test]
+    fn builds_are_parsed_from_both_header_lines() {
+        let build = GameBuild::from_log("LogInit", "Build: ++Prospect+Release-CL-123456").unwrap();
+        assert_eq!(build.changelist, 123456);
+        assert_eq!(build.version, "++Prospect+Release-CL-123456");
+        let engine = GameBuild::from_log("LogInit", "Engine Version: 4.26.2-654321+++Prospect");
+        assert_eq!(engine.map(|build| build.changelist), Some(654321));
+    }
+
+    #[test]
+    fn other_lines_and_local_builds_are_ignored() {
+        assert!(GameBuild::from_log("LogNet", "Build: ++Prospect+Release-CL-123456").is_none());
+        assert!(GameBuild::from_log("LogInit", "Build: ++Prospect+Release-CL-0").is_none());
+        assert!(GameBuild::from_log("LogInit", "Command Line: -log").is_none());
+    }
+
+    #[test]
+    fn newer_builds_outdate_the_data() {
+        assert!(build(11).is_newer_than(Some(10)));
+        assert!(!build(10).is_newer_than(Some(10)));
+        assert!(!build(9).is_newer_than(Some(10)));
+        assert!(!build(11).is_newer_than(None));
+    }
+
+    #[test]
+    fn built_in_data_is_outdated_by_newer_builds() {
+        let verified = BUILT_IN_BUILD.unwrap();
+        let timings = super::super::Timings::for_map("MAP01");
+        assert!(build(verified).outdated_data(&timings).is_empty());
+        assert_eq!(
+            build(verified + 1).outdated_data(&timings),
+            vec!["actors", "weapons", "timings (normal)"]
+        );
+    }
+}
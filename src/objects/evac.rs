@@ -0,0 +1,210 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the evac ships and the timeline of their phases.
+
+use crate::config::EvacConfig;
+
+use std::sync::Arc;
+
+/// Struct representing a single phase of an evac ship.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvacPhase {
+    /// The name of the phase.
+    pub name: String,
+    /// The duration of the phase.
+    pub duration: chrono::Duration,
+    /// The text of a log line which marks the beginning of the phase, if known.
+    pub evidence: Option<String>,
+    /// Whether the evac ship cannot be boarded anymore in this phase.
+    pub inactive: bool,
+}
+
+/// Struct representing the phases of an evac ship from the call to the departure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvacTimeline {
+    /// The phases in their order.
+    pub phases: Vec<EvacPhase>,
+}
+
+impl From<&EvacConfig> for EvacTimeline {
+    /// Creates the timeline from the configuration.
+    fn from(config: &EvacConfig) -> Self {
+        let phases = config
+            .phases
+            .iter()
+            .map(|phase| EvacPhase {
+                name: phase.name.clone(),
+                duration: chrono::Duration::seconds(phase.duration as i64),
+                evidence: phase.contains.clone(),
+                inactive: phase.inactive,
+            })
+            .collect();
+        Self { phases }
+    }
+}
+
+/// Struct representing an evac ship of the current game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evac {
+    /// The number of the evac ship in the game, starting from 1.
+    pub sequence: usize,
+    /// The actor of the evac ship in the game log, if it is known.
+    pub actor: Option<String>,
+    /// Whether the evac ship was called by the player.
+    pub own: bool,
+    /// The moment the evac ship was called.
+    pub called_at: chrono::DateTime<chrono::Utc>,
+    /// The latest phase confirmed by the game log, or the first one.
+    phase: usize,
+    /// The beginning of the confirmed phase.
+    phase_started: chrono::DateTime<chrono::Utc>,
+    /// The phases of the evac ship.
+    timeline: Arc<EvacTimeline>,
+}
+
+impl Evac {
+    /// Creates a new `Evac` instance at the beginning of its timeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The number of the evac ship in the game.
+    /// * `actor` - The actor of the evac ship in the game log, if it is known.
+    /// * `own` - Whether the evac ship was called by the player.
+    /// * `time` - The moment the evac ship was called.
+    /// * `timeline` - The phases of the evac ship.
+    ///
+    /// # Returns
+    ///
+    /// This function will return an instance of `Evac`.
+    pub fn new(
+        sequence: usize,
+        actor: Option<String>,
+        own: bool,
+        time: chrono::DateTime<chrono::Utc>,
+        timeline: Arc<EvacTimeline>,
+    ) -> Self {
+        Self {
+            sequence,
+            actor,
+            own,
+            called_at: time,
+            phase: 0,
+            phase_started: time,
+            timeline,
+        }
+    }
+
+    /// Moves the evac ship to a phase confirmed by the game log.
+    ///
+    /// # Arguments
+    ///
+    /// * `phase` - The index of the phase.
+    /// * `time` - The beginning of the phase.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn advance(&mut self, phase: usize, time: chrono::DateTime<chrono::Utc>) {
+        if phase < self.timeline.phases.len() && phase >= self.phase {
+            self.phase = phase;
+            self.phase_started = time;
+        }
+    }
+
+    /// Returns the index of the latest phase confirmed by the game log.
+    pub fn confirmed_phase(&self) -> usize {
+        self.phase
+    }
+
+    /// Returns the phase of the evac ship at the moment.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The moment.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the phase and the time until its end, or None if the evac ship has left.
+    pub fn phase_at(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<(&EvacPhase, chrono::Duration)> {
+        // The phases after the confirmed one follow by their durations
+        let mut end = self.phase_started;
+        for phase in self.timeline.phases.iter().skip(self.phase) {
+            end += phase.duration;
+            if now < end {
+                return Some((phase, end - now));
+            }
+        }
+        None
+    }
+
+    /// Returns the moment the evac ship leaves.
+    pub fn leaves_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timeline
+            .phases
+            .iter()
+            .skip(self.phase)
+            .fold(self.phase_started, |end, phase| end + phase.duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(seconds: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn evac(sequence: usize, seconds: i64) -> Evac {
+        let timeline = Arc::new(EvacTimeline::from(&EvacConfig::default()));
+        Evac::new(sequence, None, false, time(seconds), timeline)
+    }
+
+    #[test]
+    fn phases_follow_by_their_durations() {
+        let evac = evac(1, 0);
+        assert_eq!(evac.phase_at(time(5)).unwrap().0.name, "called");
+        assert_eq!(evac.phase_at(time(50)).unwrap().0.name, "landed");
+        assert_eq!(evac.leaves_at(), time(86));
+        assert!(evac.phase_at(time(86)).is_none());
+    }
+
+    #[test]
+    fn confirmed_phases_move_only_forward() {
+        let mut evac = evac(1, 0);
+        evac.advance(2, time(20));
+        assert_eq!(evac.confirmed_phase(), 2);
+        assert_eq!(
+            evac.phase_at(time(20)).unwrap(),
+            (&evac.timeline.phases[2], chrono::Duration::seconds(29))
+        );
+        assert_eq!(evac.leaves_at(), time(59));
+
+        // An older or unknown phase doesn't move the evac ship back
+        evac.advance(1, time(30));
+        evac.advance(4, time(30));
+        assert_eq!(evac.confirmed_phase(), 2);
+        assert_eq!(evac.leaves_at(), time(59));
+    }
+}
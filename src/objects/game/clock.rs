@@ -0,0 +1,321 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the storm clock, which estimates the moment the server was created
+//! from several anchors observed in the game log and manual corrections of the user.
+
+/// Enum representing the source of a clock anchor.
+#[derive(PartialEq, Clone, Debug)]
+pub enum AnchorKind {
+    /// The handshake with the server, which reports the server uptime.
+    Handshake,
+    /// A log line that marks the beginning of a phase of the day cycle.
+    Phase(String),
+}
+
+/// Struct representing a single estimate of the server creation time.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Anchor {
+    /// The source of the estimate.
+    pub kind: AnchorKind,
+    /// The estimated creation time in milliseconds since the epoch.
+    estimate: i64,
+    /// The uncertainty of the estimate in milliseconds.
+    uncertainty: i64,
+}
+
+/// Enum representing how much the clock can be trusted.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Confidence {
+    /// There are no anchors, the clock is unknown.
+    Unknown,
+    /// The clock may be off by more than a few seconds.
+    Low,
+    /// The clock is accurate to several seconds.
+    Medium,
+    /// The clock is accurate to a couple of seconds.
+    High,
+}
+
+/// Struct representing the storm clock of a game session.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct StormClock {
+    /// The anchors observed in the game log.
+    anchors: Vec<Anchor>,
+    /// The manual correction in milliseconds.
+    correction: i64,
+}
+
+impl StormClock {
+    /// The delay between the server creation and the uptime reported by the handshake.
+    const HANDSHAKE_DELAY: i64 = 5000;
+    /// The uncertainty of the handshake anchor.
+    const HANDSHAKE_UNCERTAINTY: i64 = 3000;
+    /// The uncertainty of the phase anchor.
+    const PHASE_UNCERTAINTY: i64 = 2000;
+
+    /// Adds an anchor from the handshake with the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the handshake.
+    /// * `uptime` - The server uptime in seconds reported by the handshake.
+    pub fn add_handshake(&mut self, time: chrono::DateTime<chrono::Utc>, uptime: i64) {
+        self.anchors.push(Anchor {
+            kind: AnchorKind::Handshake,
+            estimate: time.timestamp_millis() - uptime * 1000 + StormClock::HANDSHAKE_DELAY,
+            uncertainty: StormClock::HANDSHAKE_UNCERTAINTY,
+        });
+    }
+
+    /// Adds an anchor from a log line which marks the beginning of a phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the log line.
+    /// * `phase` - The name of the phase that begins.
+    /// * `timings` - The timing profile of the map.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the phase is known to the timing profile.
+    pub fn add_phase(
+        &mut self,
+        time: chrono::DateTime<chrono::Utc>,
+        phase: &str,
+        timings: &super::Timings,
+    ) -> bool {
+        // Find the position of the phase in the cycle
+        let mut phase_start = 0;
+        let mut found = false;
+        for p in timings.phases.iter() {
+            if p.name.eq_ignore_ascii_case(phase) {
+                found = true;
+                break;
+            }
+            phase_start += p.duration;
+        }
+        if !found {
+            return false;
+        }
+
+        // The phase repeats every cycle, so the estimate is only known up to a whole cycle
+        let cycle = timings.time_between_storms;
+        let candidate = time.timestamp_millis() - phase_start + timings.start_offset;
+        let estimate = match self.raw_estimate() {
+            // Take the candidate closest to the current estimate
+            Some(reference) => {
+                candidate + ((reference - candidate) as f64 / cycle as f64).round() as i64 * cycle
+            }
+            // Otherwise take the latest candidate before the log line
+            None => {
+                let ahead = (candidate - time.timestamp_millis()).max(0);
+                candidate - (ahead + cycle - 1) / cycle * cycle
+            }
+        };
+
+        self.anchors.push(Anchor {
+            kind: AnchorKind::Phase(phase.to_string()),
+            estimate,
+            uncertainty: StormClock::PHASE_UNCERTAINTY,
+        });
+        true
+    }
+
+    /// Applies a manual correction on top of the anchors.
+    ///
+    /// # Arguments
+    ///
+    /// * `seconds` - The number of seconds to shift the clock by. A positive value makes
+    ///   the countdowns longer.
+    pub fn correct(&mut self, seconds: i64) {
+        self.correction += seconds * 1000;
+    }
+
+    /// Drops the manual correction.
+    pub fn reset_correction(&mut self) {
+        self.correction = 0;
+    }
+
+    /// Returns the estimated time the server was created.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<DateTime<Utc>>` - The estimated time, or None if there are no anchors.
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let estimate = self.raw_estimate()? + self.correction;
+        chrono::DateTime::from_timestamp_millis(estimate)
+    }
+
+    /// Returns the uncertainty of the estimate.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<chrono::Duration>` - The uncertainty, or None if there are no anchors.
+    pub fn uncertainty(&self) -> Option<chrono::Duration> {
+        if self.anchors.is_empty() {
+            return None;
+        }
+
+        // The uncertainty of a weighted mean of independent estimates
+        let weight: f64 = self.anchors.iter().map(StormClock::weight).sum();
+        let mut uncertainty = 1.0 / weight.sqrt();
+
+        // The spread between the anchors means that at least one of them is wrong
+        let estimate = self.raw_estimate()?;
+        for anchor in self.anchors.iter() {
+            uncertainty = uncertainty.max((anchor.estimate - estimate).abs() as f64);
+        }
+
+        Some(chrono::Duration::milliseconds(uncertainty.round() as i64))
+    }
+
+    /// Returns how much the clock can be trusted.
+    ///
+    /// # Returns
+    ///
+    /// * `Confidence` - The confidence level of the clock.
+    pub fn confidence(&self) -> Confidence {
+        let confidence = match self.uncertainty().map(|u| u.num_seconds()) {
+            None => Confidence::Unknown,
+            Some(seconds) if seconds <= 2 => Confidence::High,
+            Some(seconds) if seconds <= 10 => Confidence::Medium,
+            Some(_) => Confidence::Low,
+        };
+
+        // Without the handshake the phase anchors only guess the cycle, so the creation time
+        // and the server end may be off by whole cycles
+        let pinned = self
+            .anchors
+            .iter()
+            .any(|anchor| anchor.kind == AnchorKind::Handshake);
+        if pinned {
+            confidence
+        } else {
+            confidence.min(Confidence::Low)
+        }
+    }
+
+    /// Combines all anchors into a single estimate without the manual correction.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The weighted mean of the anchors, or None if there are no anchors.
+    fn raw_estimate(&self) -> Option<i64> {
+        let first = self.anchors.first()?;
+
+        // Average the offsets from the first anchor to avoid overflows
+        let weight: f64 = self.anchors.iter().map(StormClock::weight).sum();
+        let offset: f64 = self
+            .anchors
+            .iter()
+            .map(|anchor| (anchor.estimate - first.estimate) as f64 * StormClock::weight(anchor))
+            .sum();
+
+        Some(first.estimate + (offset / weight).round() as i64)
+    }
+
+    /// Returns the weight of the anchor, which is the inverse of its variance.
+    fn weight(anchor: &Anchor) -> f64 {
+        1.0 / (anchor.uncertainty as f64).powi(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Timings;
+    use super::*;
+
+    fn time(millis: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp_millis(1_700_000_000_000 + millis).unwrap()
+    }
+
+    /// Provides the clock with a handshake which estimates the creation at `time(0)`.
+    fn handshake() -> StormClock {
+        let mut clock = StormClock::default();
+        clock.add_handshake(time(600_000 - StormClock::HANDSHAKE_DELAY), 600);
+        clock
+    }
+
+    #[test]
+    fn handshake_only() {
+        let clock = handshake();
+        assert_eq!(clock.created_at(), Some(time(0)));
+        assert_eq!(clock.uncertainty(), Some(chrono::Duration::seconds(3)));
+        assert_eq!(clock.confidence(), Confidence::Medium);
+    }
+
+    #[test]
+    fn phase_anchors_only_are_capped_at_low() {
+        let timings = Timings::for_map("MAP01");
+        let mut clock = StormClock::default();
+        // The day begins at the creation of the server, so it tells the moment of a cycle
+        assert!(clock.add_phase(time(timings.time_between_storms), "Day", &timings));
+        assert!(clock.add_phase(time(2 * timings.time_between_storms), "day", &timings));
+        assert!(!clock.add_phase(time(0), "Storm", &timings));
+        assert_eq!(
+            clock.uncertainty(),
+            Some(chrono::Duration::milliseconds(1414))
+        );
+        assert_eq!(clock.confidence(), Confidence::Low);
+    }
+
+    #[test]
+    fn conflicting_anchors_are_weighted_by_their_uncertainty() {
+        let timings = Timings::for_map("MAP01");
+        let mut clock = handshake();
+        assert!(clock.add_phase(time(2600), "Day", &timings));
+        // The weights are 1/3² and 1/2², so the phase anchor takes 9/13 of the difference
+        assert_eq!(clock.created_at(), Some(time(1800)));
+        assert_eq!(
+            clock.uncertainty(),
+            Some(chrono::Duration::milliseconds(1800))
+        );
+        assert_eq!(clock.confidence(), Confidence::High);
+    }
+
+    #[test]
+    fn late_anchors_correct_the_drift() {
+        let timings = Timings::for_map("MAP01");
+        let mut clock = handshake();
+        clock.correct(5);
+        // A phase several cycles later belongs to the cycle closest to the estimate
+        let late = time(3 * timings.time_between_storms + 1300);
+        assert!(clock.add_phase(late, "Day", &timings));
+        assert_eq!(clock.created_at(), Some(time(900 + 5000)));
+        clock.reset_correction();
+        assert_eq!(clock.created_at(), Some(time(900)));
+    }
+
+    #[test]
+    fn no_handshake_is_never_the_epoch() {
+        let timings = Timings::for_map("MAP01");
+        let mut clock = StormClock::default();
+        assert_eq!(clock.created_at(), None);
+        assert_eq!(clock.confidence(), Confidence::Unknown);
+
+        // Without a reference the latest cycle before the log line is taken
+        assert!(clock.add_phase(time(0), "Night", &timings));
+        let created_at = clock.created_at().unwrap();
+        assert!(created_at <= time(0));
+        assert!(created_at > time(-timings.time_between_storms));
+        assert!(created_at.timestamp() > 0);
+    }
+}
@@ -44,6 +44,19 @@ impl GameMap {
             GameMap::TharisIsland(timings) => timings,
         }
     }
+
+    /// Returns the name of the game map.
+    ///
+    /// # Return
+    ///
+    /// This function will return the name as shown in the game.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMap::BrightSands(_) => "Bright Sands",
+            GameMap::CrescentFalls(_) => "Crescent Falls",
+            GameMap::TharisIsland(_) => "Tharis Island",
+        }
+    }
 }
 
 impl Default for GameMap {
@@ -51,8 +64,8 @@ impl Default for GameMap {
     ///
     /// # Return
     ///
-    /// This function will return an instance of `GameMap::BrightSands` with normal timings.
+    /// This function will return an instance of `GameMap::BrightSands` with the fallback timings.
     fn default() -> Self {
-        GameMap::BrightSands(super::NORMAL.clone())
+        GameMap::BrightSands(super::Timings::default())
     }
 }
@@ -0,0 +1,64 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the meteor showers of a game session, which are kept with their moment in
+//! the day cycle to compare their timing with the storms.
+
+/// Struct representing a meteor shower in a game session.
+#[derive(PartialEq, Clone, Debug)]
+pub struct MeteorShower {
+    /// The time of the meteor shower in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
+    /// The phase of the day cycle during the meteor shower, if the storm clock is known.
+    pub phase: Option<String>,
+    /// The time since the beginning of the phase, if the storm clock is known.
+    pub into_phase: Option<chrono::Duration>,
+}
+
+impl MeteorShower {
+    /// Creates a new `MeteorShower` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the meteor shower in the game log.
+    /// * `clock` - The storm clock of the game session.
+    /// * `timings` - The timing profile of the map.
+    ///
+    /// # Return
+    ///
+    /// This function will return an instance of `MeteorShower`.
+    pub fn new(
+        time: chrono::DateTime<chrono::Utc>,
+        clock: &super::StormClock,
+        timings: &super::Timings,
+    ) -> Self {
+        // The cycle begins at the start offset before the creation of the server
+        let phase = clock.created_at().and_then(|created_at| {
+            let offset = (time - created_at).num_milliseconds() + timings.start_offset;
+            timings.phase_at(offset)
+        });
+
+        Self {
+            time,
+            phase: phase.map(|(phase, _)| phase.name.clone()),
+            into_phase: phase.map(|(_, millis)| chrono::Duration::milliseconds(millis)),
+        }
+    }
+}
@@ -20,34 +20,53 @@
 
 //! This module contains game session definitions.
 
+mod clock;
 mod map;
+mod meteors;
+mod options;
 mod timings;
 
+pub use clock::{Confidence, StormClock};
 pub use map::GameMap;
-pub use timings::{Timings, NORMAL, THARIS};
+pub use meteors::MeteorShower;
+pub use options::MatchOptions;
+pub use timings::Timings;
+
+use super::{ActorInstanceId, GameBuild, InstanceId, Region};
 
 /// Struct representing a game session.
 #[derive(PartialEq, Clone, Debug)]
 pub struct Game {
     /// The ID of the game instance.
-    pub instance_id: String,
+    pub instance_id: InstanceId,
     ///  The region of the game.
-    pub region: String,
+    pub region: Region,
     /// The name of the game.
     pub name: String,
     /// The map of the game.
     pub map: GameMap,
-    /// The time when the game session was created.
-    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// The storm clock, which estimates when the game session was created.
+    pub clock: StormClock,
+    /// The options of the match.
+    pub options: MatchOptions,
+    /// The build of the game client, if it was found in the log header.
+    pub build: Option<GameBuild>,
     /// The size of the party.
     pub party_size: usize,
     /// The total number of players.
     pub total_players: usize,
     /// The number of nearby players.
     pub near_players: usize,
-    /// The kill count of each player, stored in a HashMap where the keys are player names and the
-    /// values are the corresponding kill counts.
-    kill_count: std::collections::HashMap<String, usize>,
+    /// The moment the player was welcomed by the server.
+    pub joined: chrono::DateTime<chrono::Utc>,
+    /// The changes of the number of nearby players with their times, the oldest first.
+    near_history: Vec<(chrono::DateTime<chrono::Utc>, usize)>,
+    /// The meteor showers on the server, the oldest first. They are kept when the player leaves,
+    /// as they belong to the server rather than to the match.
+    meteors: Vec<MeteorShower>,
+    /// The kill count of each actor, stored in a HashMap where the keys are actor instances and
+    /// the values are the corresponding kill counts.
+    kill_count: std::collections::HashMap<ActorInstanceId, usize>,
 }
 
 impl Game {
@@ -58,31 +77,26 @@ impl Game {
     /// * `instance_id` - The ID of the game instance.
     /// * `region` - The region of the game.
     /// * `map` - The map of the game.
-    /// * `created_at` - The time when the game was created.
-    /// * `party_size` - The size of the party.
+    /// * `clock` - The storm clock of the game.
+    /// * `options` - The options of the match.
+    /// * `build` - The build of the game client, if known.
+    /// * `joined` - The moment the player was welcomed by the server.
     ///
     /// # Return
     ///
     /// This function will return an instance of `Game`.
     pub fn new(
-        instance_id: String,
-        region: String,
+        instance_id: InstanceId,
+        region: Region,
         map: GameMap,
-        created_at: chrono::DateTime<chrono::Utc>,
-        party_size: usize,
+        clock: StormClock,
+        options: MatchOptions,
+        build: Option<GameBuild>,
+        joined: chrono::DateTime<chrono::Utc>,
     ) -> Self {
         // Generate a name for the game from `instance_id` and my own fake name generator.
-        let name = if let Some(id) = instance_id.split('-').last() {
-            if !id.is_empty() {
-                let seed = u64::from_str_radix(id, 16).unwrap();
-                let rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(seed);
-                crate::utils::fake_name(rng).to_string()
-            } else {
-                String::new()
-            }
-        } else {
-            String::new()
-        };
+        let rng: rand::rngs::StdRng = rand::SeedableRng::seed_from_u64(instance_id.number());
+        let name = crate::utils::fake_name(rng);
 
         // Create and return the game
         Self {
@@ -90,10 +104,16 @@ impl Game {
             region,
             name,
             map,
-            created_at,
-            party_size,
+            clock,
+            // Players without a squad don't have the squad size in the options
+            party_size: options.squad_size.unwrap_or(1),
+            options,
+            build,
             total_players: 0,
             near_players: 0,
+            joined,
+            near_history: Vec::new(),
+            meteors: Vec::new(),
             kill_count: std::collections::HashMap::new(),
         }
     }
@@ -105,11 +125,91 @@ impl Game {
         // Reset player counts
         self.total_players = 0;
         self.near_players = 0;
+        self.near_history.clear();
     }
 
-    pub fn kill(&mut self, id: String) -> usize {
+    /// Counts a kill of the actor instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the actor instance that killed a player.
+    ///
+    /// # Return
+    ///
+    /// This function will return the number of kills of the actor instance in this game.
+    pub fn kill(&mut self, id: ActorInstanceId) -> usize {
         let count = self.kill_count.entry(id).or_insert(0);
         *count += 1;
         *count
     }
+
+    /// Records the current number of nearby players.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the change in the game log.
+    pub fn record_near_players(&mut self, time: chrono::DateTime<chrono::Utc>) {
+        self.near_history.push((time, self.near_players));
+    }
+
+    /// Returns the number of nearby players over a period.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The beginning of the period.
+    ///
+    /// # Return
+    ///
+    /// This function will return the changes after the beginning, preceded by the number of
+    /// nearby players at the beginning if it was recorded.
+    pub fn near_players_since(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<(chrono::DateTime<chrono::Utc>, usize)> {
+        let first = self.near_history.partition_point(|(time, _)| *time < from);
+        let mut history = Vec::new();
+        if first > 0 {
+            history.push((from, self.near_history[first - 1].1));
+        }
+        history.extend_from_slice(&self.near_history[first..]);
+        history
+    }
+
+    /// Records a meteor shower on the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the meteor shower in the game log.
+    ///
+    /// # Return
+    ///
+    /// This function will return the number of meteor showers on the server so far.
+    pub fn record_meteors(&mut self, time: chrono::DateTime<chrono::Utc>) -> usize {
+        let shower = MeteorShower::new(time, &self.clock, self.map.timings());
+        self.meteors.push(shower);
+        self.meteors.len()
+    }
+
+    /// Returns the meteor showers on the server, the oldest first.
+    pub fn meteors(&self) -> &[MeteorShower] {
+        &self.meteors
+    }
+
+    /// Returns the actor instances which killed players in this game.
+    ///
+    /// # Return
+    ///
+    /// This function will return the actor instances with their kill counts, the most kills first.
+    pub fn leaderboard(&self) -> Vec<(ActorInstanceId, usize)> {
+        let mut leaderboard: Vec<(ActorInstanceId, usize)> = self
+            .kill_count
+            .iter()
+            .map(|(id, count)| (id.clone(), *count))
+            .collect();
+        leaderboard.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+        });
+        leaderboard
+    }
 }
@@ -0,0 +1,137 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains options of a match, which are passed in the travel URL.
+
+use std::collections::BTreeMap;
+
+/// Struct representing the options of a match, e.g. `/Game/Maps/MP/MAP01?SquadSize=2?Game=...`.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct MatchOptions {
+    /// The size of the squad.
+    pub squad_size: Option<usize>,
+    /// The mode of the match.
+    pub mode: Option<String>,
+    /// Options which are not recognized, kept for diagnostics.
+    pub unknown: BTreeMap<String, String>,
+}
+
+impl MatchOptions {
+    /// Parses the options from a travel URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL with options separated by `?`. Options without a value are allowed.
+    ///
+    /// # Return
+    ///
+    /// This function will return the options found in the URL.
+    pub fn from_url(url: &str) -> Self {
+        let mut options = Self::default();
+
+        // Everything before the first `?` is the path of the map
+        for option in url.split('?').skip(1) {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            // The last option may be followed by the rest of the log line
+            let value = value.split_whitespace().next().unwrap_or_default();
+            let key = key.trim();
+            if key.is_empty() {
+                continue;
+            }
+
+            match key.to_lowercase().as_str() {
+                "squadsize" => match value.parse() {
+                    Ok(size) => options.squad_size = Some(size),
+                    Err(_) => {
+                        log::error!("Invalid squad size: {:?}", value);
+                        options.unknown.insert(key.to_string(), value.to_string());
+                    }
+                },
+                "game" | "gamemode" | "mode" => options.mode = Some(MatchOptions::mode_name(value)),
+                _ => {
+                    options.unknown.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        options
+    }
+
+    /// Returns a readable name of the mode, e.g. `/Script/Prospect.YGameMode_Match` becomes
+    /// `YGameMode_Match`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mode as written in the URL.
+    ///
+    /// # Return
+    ///
+    /// This function will return the last part of the mode path.
+    fn mode_name(value: &str) -> String {
+        value
+            .rsplit(['/', '.'])
+            .next()
+            .unwrap_or(value)
+            .trim_end_matches("_C")
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_options() {
+        let options = MatchOptions::from_url(
+            "/Game/Maps/MP/MAP01?SquadSize=2?Game=/Script/Prospect.YGameMode_Match_C",
+        );
+        assert_eq!(options.squad_size, Some(2));
+        assert_eq!(options.mode.as_deref(), Some("YGameMode_Match"));
+        assert!(options.unknown.is_empty());
+    }
+
+    #[test]
+    fn ignores_the_rest_of_the_log_line() {
+        let options = MatchOptions::from_url("/Game/Maps/MP/MAP01?SquadSize=3 took 12ms");
+        assert_eq!(options.squad_size, Some(3));
+    }
+
+    #[test]
+    fn keeps_unknown_and_invalid_options() {
+        let options = MatchOptions::from_url("/Game/Maps/MP/MAP01?SquadSize=two?Listen??Foo=bar");
+        assert_eq!(options.squad_size, None);
+        assert_eq!(
+            options.unknown.get("SquadSize").map(String::as_str),
+            Some("two")
+        );
+        assert_eq!(options.unknown.get("Listen").map(String::as_str), Some(""));
+        assert_eq!(options.unknown.get("Foo").map(String::as_str), Some("bar"));
+        assert_eq!(options.unknown.len(), 3);
+    }
+
+    #[test]
+    fn parses_url_without_options() {
+        assert_eq!(
+            MatchOptions::from_url("/Game/Maps/MP/MAP01"),
+            MatchOptions::default()
+        );
+    }
+}
@@ -18,55 +18,253 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-//! This module contains timings for different types of maps.
+//! This module contains timing profiles of the day cycle for different types of maps.
+//! The built-in profiles can be extended or replaced by the user configuration.
 
-/// Struct representing various timings.
+use lazy_static::lazy_static;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Struct representing a single phase of the day cycle.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Phase {
+    /// The name of the phase.
+    pub name: String,
+    /// The duration of the phase in milliseconds.
+    pub duration: i64,
+    /// The color used to display the phase.
+    pub color: egui::Color32,
+}
+
+/// Struct representing a timing profile of the day cycle.
 #[derive(PartialEq, Clone, Debug)]
 pub struct Timings {
+    /// The name of the profile.
+    pub name: String,
+    /// The names of the maps in the game logs which use this profile.
+    pub maps: Vec<String>,
     /// The total time between storms.
     pub time_between_storms: i64,
-    /// The duration of the morning.
-    pub morning: i64,
-    /// The duration of the day.
-    pub day: i64,
-    /// The duration of the evening.
-    pub evening: i64,
-    /// The duration of the night.
-    pub night: i64,
+    /// The offset in the cycle at the moment the server is created.
+    pub start_offset: i64,
+    /// The phases of the cycle in their order.
+    pub phases: Vec<Phase>,
+    /// The changelist of the latest game build the profile was verified against, if known.
+    pub verified_build: crate::objects::build::VerifiedBuild,
 }
 
 impl Timings {
-    /// Creates a new `Timings` instance.
+    /// The name of the profile used when nothing else matches.
+    const FALLBACK: &'static str = "normal";
+
+    /// Creates a new `Timings` instance, verified against the build of the built-in data.
     ///
     /// # Arguments
     ///
-    /// * `morning` - The duration of the morning.
-    /// * `day` - The duration of the day.
-    /// * `evening` - The duration of the evening.
-    /// * `night` - The duration of the night.
+    /// * `name` - The name of the profile.
+    /// * `maps` - The names of the maps in the game logs which use this profile.
+    /// * `phases` - The phases of the cycle in their order.
+    /// * `start_phase` - The index of the phase that begins when the server is created.
     ///
     /// # Return
     ///
     /// This function will return an instance of `Timings`.
-    fn new(morning: i64, day: i64, evening: i64, night: i64) -> Self {
-        // Calculate the time between storms as the sum of all other times
-        let time_between_storms = morning + day + evening + night;
+    fn new(name: &str, maps: Vec<String>, phases: Vec<Phase>, start_phase: usize) -> Self {
+        // Calculate the time between storms as the sum of all phases
+        let time_between_storms = phases.iter().map(|phase| phase.duration).sum();
+        // Calculate the offset as the sum of all phases before the start one
+        let start_offset = phases
+            .iter()
+            .take(start_phase)
+            .map(|phase| phase.duration)
+            .sum();
 
         Self {
+            name: name.to_string(),
+            maps,
             time_between_storms,
-            morning,
-            day,
-            evening,
-            night,
+            start_offset,
+            phases,
+            verified_build: crate::objects::build::BUILT_IN_BUILD,
+        }
+    }
+
+    /// Retrieves a timing profile used by the map.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - The name of the map in the game logs.
+    ///
+    /// # Return
+    ///
+    /// This function will return the profile which declares the map, or the fallback profile
+    /// if no profile declares it.
+    pub fn for_map(map: &str) -> Self {
+        let timings = TIMINGS.read().unwrap();
+        timings
+            .values()
+            .find(|timings| timings.maps.iter().any(|m| m.eq_ignore_ascii_case(map)))
+            .unwrap_or(&timings[Timings::FALLBACK])
+            .clone()
+    }
+
+    /// Finds the phase at a moment of the cycle.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The time since the beginning of the cycle in milliseconds.
+    ///
+    /// # Return
+    ///
+    /// This function will return the phase with the time since its beginning in milliseconds,
+    /// or None if the profile has no phases.
+    pub fn phase_at(&self, offset: i64) -> Option<(&Phase, i64)> {
+        let mut start = 0;
+        let offset = offset.rem_euclid(self.time_between_storms.max(1));
+        for phase in self.phases.iter() {
+            if offset < start + phase.duration {
+                return Some((phase, offset - start));
+            }
+            start += phase.duration;
+        }
+        None
+    }
+
+    /// Registers timing profiles from the user configuration. A profile with the name of
+    /// an existing one replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `profiles` - The profiles from the configuration.
+    pub fn register(profiles: &[crate::config::TimingProfile]) {
+        let mut timings = TIMINGS.write().unwrap();
+        for profile in profiles {
+            match Timings::try_from(profile) {
+                Ok(profile) => {
+                    // The maps of the new profile are no longer used by the other ones
+                    for other in timings.values_mut() {
+                        other
+                            .maps
+                            .retain(|m| !profile.maps.iter().any(|p| p.eq_ignore_ascii_case(m)));
+                    }
+                    log::info!("Timing profile {:?} registered", profile.name);
+                    timings.insert(profile.name.to_lowercase(), profile);
+                }
+                Err(e) => log::error!("Invalid timing profile {:?}: {}", profile.name, e),
+            }
         }
     }
 }
 
-lazy_static::lazy_static! {
-    /// Normal timings.
-    pub static ref NORMAL: Timings = Timings::new(duration(4, 0), duration(16, 40), duration(13, 20), duration(4, 40));
-    /// Tharis map timings.
-    pub static ref THARIS: Timings = Timings::new(duration(4, 0), duration(12, 40), duration(8, 20), duration(4, 40));
+impl Default for Timings {
+    /// Provides the fallback timing profile.
+    ///
+    /// # Return
+    ///
+    /// This function will return the profile with normal timings.
+    fn default() -> Self {
+        let timings = TIMINGS.read().unwrap();
+        timings[Timings::FALLBACK].clone()
+    }
+}
+
+impl TryFrom<&crate::config::TimingProfile> for Timings {
+    type Error = String;
+
+    /// Converts a configured timing profile into `Timings`.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile from the configuration.
+    ///
+    /// # Return
+    ///
+    /// This function will return the profile or a description of why it is invalid.
+    fn try_from(profile: &crate::config::TimingProfile) -> Result<Self, Self::Error> {
+        if profile.phases.is_empty() {
+            return Err("no phases defined".to_string());
+        }
+        if let Some(phase) = profile.phases.iter().find(|phase| phase.duration == 0) {
+            return Err(format!("phase {:?} has zero duration", phase.name));
+        }
+
+        // Find the phase that begins when the server is created
+        let start_phase = match &profile.start_phase {
+            Some(name) => profile
+                .phases
+                .iter()
+                .position(|phase| phase.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("unknown start phase {:?}", name))?,
+            None => 0,
+        };
+
+        let phases = profile
+            .phases
+            .iter()
+            .map(|phase| Phase {
+                name: phase.name.clone(),
+                duration: duration(0, phase.duration),
+                color: phase.color.into(),
+            })
+            .collect();
+
+        Ok(Timings {
+            verified_build: profile.verified_build,
+            ..Timings::new(&profile.name, profile.maps.clone(), phases, start_phase)
+        })
+    }
+}
+
+lazy_static! {
+    /// Store all timing profiles in a thread-safe data structure.
+    #[derive(Debug)]
+    static ref TIMINGS: RwLock<HashMap<String, Timings>> = {
+        let mut timings = HashMap::new();
+
+        /// Helper function to create a phase.
+        fn create_phase(name: &str, duration: i64, color: egui::Color32) -> Phase {
+            Phase {
+                name: name.to_string(),
+                duration,
+                color,
+            }
+        }
+
+        /// Helper function to create the standard four phases.
+        fn create_phases(morning: i64, day: i64, evening: i64, night: i64) -> Vec<Phase> {
+            vec![
+                create_phase("Morning", morning, egui::Color32::from_rgb(0x00, 0xcc, 0xff)),
+                create_phase("Day", day, egui::Color32::from_rgb(0xff, 0xff, 0x00)),
+                create_phase("Evening", evening, egui::Color32::from_rgb(0xff, 0xef, 0xd5)),
+                create_phase("Night", night, egui::Color32::from_rgb(0xff, 0x00, 0x99)),
+            ]
+        }
+
+        // List of built-in profiles, the servers are created at the beginning of the day.
+        let timings_list = vec![
+            Timings::new(
+                "normal",
+                vec!["MAP01".to_string(), "MAP02".to_string()],
+                create_phases(duration(4, 0), duration(16, 40), duration(13, 20), duration(4, 40)),
+                1,
+            ),
+            Timings::new(
+                "tharis",
+                vec!["AlienCaverns".to_string()],
+                create_phases(duration(4, 0), duration(12, 40), duration(8, 20), duration(4, 40)),
+                1,
+            ),
+        ];
+
+        // Insert each profile into the HashMap.
+        for profile in timings_list {
+            timings.insert(profile.name.to_lowercase(), profile);
+        }
+
+        // Return the HashMap as a RwLock for thread-safety.
+        RwLock::new(timings)
+    };
 }
 
 /// Returns the total duration in milliseconds for a given amount of minutes and seconds.
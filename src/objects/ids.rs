@@ -0,0 +1,341 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains validated identifiers found in the game logs.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Struct representing the ID of a game instance, e.g. `...-1a2b3c4d`.
+/// The part after the last dash is a hexadecimal number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstanceId {
+    /// The ID as written in the game logs.
+    raw: String,
+    /// The hexadecimal number at the end of the ID.
+    number: u64,
+}
+
+impl InstanceId {
+    /// The number of hexadecimal digits in the short form of the ID.
+    const SHORT_LENGTH: usize = 6;
+
+    /// Returns the hexadecimal number at the end of the ID.
+    ///
+    /// # Return
+    ///
+    /// This function will return the number, which is used as a seed for the game name.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the short form of the ID.
+    ///
+    /// # Return
+    ///
+    /// This function will return the last hexadecimal digits of the ID.
+    pub fn short(&self) -> &str {
+        let hex = self.raw.rsplit('-').next().unwrap_or_default();
+        &hex[hex.len().saturating_sub(InstanceId::SHORT_LENGTH)..]
+    }
+}
+
+impl FromStr for InstanceId {
+    type Err = String;
+
+    /// Parses and validates the ID of a game instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The ID as written in the game logs.
+    ///
+    /// # Return
+    ///
+    /// This function will return the ID, or an error message if it is malformed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.rsplit('-').next().unwrap_or_default();
+        if hex.is_empty() || hex.len() > 16 {
+            return Err(format!("malformed instance id: {:?}", s));
+        }
+
+        let number =
+            u64::from_str_radix(hex, 16).map_err(|_| format!("malformed instance id: {:?}", s))?;
+
+        Ok(Self {
+            raw: s.to_string(),
+            number,
+        })
+    }
+}
+
+impl fmt::Display for InstanceId {
+    /// Formats the ID as written in the game logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// Enum representing the region of a game server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Region {
+    /// Servers in Europe.
+    Europe,
+    /// Servers in the east of North America.
+    NorthAmericaEast,
+    /// Servers in the west of North America.
+    NorthAmericaWest,
+    /// Servers in South America.
+    SouthAmerica,
+    /// Servers in Asia.
+    Asia,
+    /// Servers in Oceania.
+    Oceania,
+    /// Any other region, as written in the game logs.
+    Other(String),
+}
+
+impl Region {
+    /// Returns the friendly name of the region.
+    ///
+    /// # Return
+    ///
+    /// This function will return a name like `North America East`.
+    pub fn name(&self) -> &str {
+        match self {
+            Region::Europe => "Europe",
+            Region::NorthAmericaEast => "North America East",
+            Region::NorthAmericaWest => "North America West",
+            Region::SouthAmerica => "South America",
+            Region::Asia => "Asia",
+            Region::Oceania => "Oceania",
+            Region::Other(region) => region,
+        }
+    }
+
+    /// Returns the short code of the region.
+    ///
+    /// # Return
+    ///
+    /// This function will return a code like `NA-E`.
+    pub fn short(&self) -> &str {
+        match self {
+            Region::Europe => "EU",
+            Region::NorthAmericaEast => "NA-E",
+            Region::NorthAmericaWest => "NA-W",
+            Region::SouthAmerica => "SA",
+            Region::Asia => "AS",
+            Region::Oceania => "OCE",
+            Region::Other(region) => region,
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    /// Parses the region of a game server. Unknown regions are kept as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The region as written in the game logs, e.g. `eu-central-1` or `NA`.
+    ///
+    /// # Return
+    ///
+    /// This function will return the region, or an error message if it is empty.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let region = s.trim().to_lowercase();
+        if region.is_empty() {
+            return Err("empty region".to_string());
+        }
+
+        // Oceania is checked before Asia, because both may start with `ap-`
+        let region = match region.as_str() {
+            r if r.starts_with("oce") || r.starts_with("au") || r == "ap-southeast-2" => {
+                Region::Oceania
+            }
+            r if r.starts_with("eu") => Region::Europe,
+            r if r.starts_with("us-west") || r.starts_with("na-w") || r == "naw" => {
+                Region::NorthAmericaWest
+            }
+            r if r.starts_with("us") || r.starts_with("na") => Region::NorthAmericaEast,
+            r if r.starts_with("sa") => Region::SouthAmerica,
+            r if r.starts_with("as") || r.starts_with("ap") => Region::Asia,
+            _ => Region::Other(s.trim().to_string()),
+        };
+
+        Ok(region)
+    }
+}
+
+impl fmt::Display for Region {
+    /// Formats the friendly name of the region, or the short code with the alternate flag.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", self.short())
+        } else {
+            write!(f, "{}", self.name())
+        }
+    }
+}
+
+/// Struct representing the ID of an actor instance in the game, e.g. `AIChar_Strider_BP_C_2147`.
+/// It consists of the class of the actor and the number of its instance.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActorInstanceId {
+    /// The class of the actor, e.g. `AIChar_Strider_BP`.
+    class: String,
+    /// The number of the actor instance.
+    instance: String,
+}
+
+impl ActorInstanceId {
+    /// The delimiter between the class and the instance.
+    const DELIMITER: &'static str = "_C_";
+
+    /// Returns the class of the actor.
+    ///
+    /// # Return
+    ///
+    /// This function will return the class name, which is the actor name in the game logs.
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+}
+
+impl FromStr for ActorInstanceId {
+    type Err = String;
+
+    /// Parses and validates the ID of an actor instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The ID as written in the game logs.
+    ///
+    /// # Return
+    ///
+    /// This function will return the ID, or an error message if it is malformed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once(ActorInstanceId::DELIMITER) {
+            Some((class, instance)) if !class.is_empty() && !instance.is_empty() => Ok(Self {
+                class: class.to_string(),
+                instance: instance.to_string(),
+            }),
+            _ => Err(format!("malformed actor instance id: {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ActorInstanceId {
+    /// Formats the ID as written in the game logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.class,
+            ActorInstanceId::DELIMITER,
+            self.instance
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_instance_id() {
+        let id = "Prospect-Sample-1a2b3c4d".parse::<InstanceId>().unwrap();
+        assert_eq!(id.number(), 0x1a2b3c4d);
+        assert_eq!(id.short(), "2b3c4d");
+        assert_eq!(id.to_string(), "Prospect-Sample-1a2b3c4d");
+    }
+
+    #[test]
+    fn keeps_short_form_of_short_instance_id() {
+        let id = "abc".parse::<InstanceId>().unwrap();
+        assert_eq!(id.number(), 0xabc);
+        assert_eq!(id.short(), "abc");
+    }
+
+    #[test]
+    fn rejects_malformed_instance_ids() {
+        for id in [
+            "",
+            "Prospect-",
+            "Prospect-xyz",
+            "Prospect-12345678901234567",
+        ] {
+            assert!(id.parse::<InstanceId>().is_err(), "{:?}", id);
+        }
+    }
+
+    #[test]
+    fn parses_regions() {
+        assert_eq!("eu-central-1".parse::<Region>(), Ok(Region::Europe));
+        assert_eq!("us-east-2".parse::<Region>(), Ok(Region::NorthAmericaEast));
+        assert_eq!("us-west-1".parse::<Region>(), Ok(Region::NorthAmericaWest));
+        assert_eq!("ap-southeast-2".parse::<Region>(), Ok(Region::Oceania));
+        assert_eq!("ap-northeast-1".parse::<Region>(), Ok(Region::Asia));
+        assert_eq!(
+            " Mars ".parse::<Region>(),
+            Ok(Region::Other("Mars".to_string()))
+        );
+        assert!("  ".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn round_trips_short_region_codes() {
+        let regions = [
+            Region::Europe,
+            Region::NorthAmericaEast,
+            Region::NorthAmericaWest,
+            Region::SouthAmerica,
+            Region::Asia,
+            Region::Oceania,
+            Region::Other("Mars".to_string()),
+        ];
+        for region in regions {
+            assert_eq!(format!("{:#}", region).parse::<Region>(), Ok(region));
+        }
+    }
+
+    #[test]
+    fn parses_actor_instance_id() {
+        let id = "AIChar_Strider_BP_C_2147"
+            .parse::<ActorInstanceId>()
+            .unwrap();
+        assert_eq!(id.class(), "AIChar_Strider_BP");
+        assert_eq!(id.to_string(), "AIChar_Strider_BP_C_2147");
+    }
+
+    #[test]
+    fn splits_actor_instance_id_at_last_delimiter() {
+        let id = "Some_C_Actor_C_7".parse::<ActorInstanceId>().unwrap();
+        assert_eq!(id.class(), "Some_C_Actor");
+        assert_eq!(id.to_string(), "Some_C_Actor_C_7");
+    }
+
+    #[test]
+    fn rejects_malformed_actor_instance_ids() {
+        for id in ["", "None", "_C_2147", "AIChar_Strider_BP_C_"] {
+            assert!(id.parse::<ActorInstanceId>().is_err(), "{:?}", id);
+        }
+    }
+}
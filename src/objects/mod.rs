@@ -21,15 +21,24 @@
 //! This module contains all objects in the game.
 
 mod actors;
+mod build;
+mod evac;
 mod game;
+mod ids;
 mod weapons;
 
 pub use actors::Actor;
-pub use game::{Game, GameMap, Timings, NORMAL, THARIS};
+pub use build::GameBuild;
+pub use evac::{Evac, EvacTimeline};
+pub use game::{Confidence, Game, GameMap, MatchOptions, MeteorShower, StormClock, Timings};
+pub use ids::{ActorInstanceId, InstanceId, Region};
 pub use weapons::Weapon;
 
-/// Enum representing the rarity of a game item.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+use serde::{Deserialize, Serialize};
+
+/// Enum representing the rarity of a game item, ordered from the most basic to the rarest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Rarity {
     /// The most basic rarity.
     Common,
@@ -46,27 +55,3 @@ pub enum Rarity {
     /// The most rare items.
     Rainbow,
 }
-
-// Implement the From trait to convert Rarity to egui::Color32.
-impl From<Rarity> for egui::Color32 {
-    /// Convert a Rarity value to a Color32 value.
-    ///
-    /// # Arguments
-    ///
-    /// * `value` - The Rarity to convert.
-    ///
-    /// # Return
-    ///
-    /// This function will return a Color32 value corresponding to the Rarity.
-    fn from(value: Rarity) -> Self {
-        match value {
-            Rarity::Common => egui::Color32::from_rgb(0x97, 0x9a, 0x9a),
-            Rarity::Uncommon => egui::Color32::from_rgb(0x58, 0xd6, 0x8d),
-            Rarity::Rare => egui::Color32::from_rgb(0x04, 0x95, 0xb4),
-            Rarity::Epic => egui::Color32::from_rgb(0xb5, 0x84, 0xc8),
-            Rarity::Exotic => egui::Color32::from_rgb(0xe7, 0x4c, 0x3c),
-            Rarity::Legendary => egui::Color32::from_rgb(0xff, 0x80, 0x80),
-            Rarity::Rainbow => egui::Color32::GOLD,
-        }
-    }
-}
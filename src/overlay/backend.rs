@@ -19,24 +19,22 @@
 // SOFTWARE.
 
 //! This module provides the `Backend` struct, which sets up and manages the graphical window
-//! using the `winit` and `wgpu` libraries, including configuring the window's parameters,
+//! using the `winit` library and one of the renderers, including configuring the window's parameters,
 //! enabling a custom font with `egui`, and making the window transparent and unresponsive
 //! to mouse events. The Overlay operates on top of the `egui` library.
 
 use super::events::Action;
 
-use winit::platform::windows::WindowExtWindows;
-
 /// The `Backend` struct is used for creating and managing the graphical window.
 pub struct Backend {
     pub window: winit::window::Window,
-    pub event_loop: winit::event_loop::EventLoop<Action>,
     pub platform: egui_winit_platform::Platform,
-    pub surface: wgpu::Surface,
-    pub surface_config: wgpu::SurfaceConfiguration,
-    pub device: wgpu::Device,
-    pub queue: wgpu::Queue,
-    pub egui_rpass: egui_wgpu_backend::RenderPass,
+    /// The renderer of the `egui` output, on the GPU or on the CPU.
+    pub renderer: super::renderer::Renderer,
+    /// The platform-specific setup of the window.
+    pub window_platform: Box<dyn super::platform::WindowPlatform>,
+    /// The scale of the fonts and the window relative to a 1080p monitor.
+    pub scale: f32,
 }
 
 impl Backend {
@@ -44,76 +42,73 @@ impl Backend {
     ///
     /// # Arguments
     ///
-    /// * `width` - Desired window width.
-    /// * `height` - Desired window height.
+    /// * `width` - Desired window width, before scaling.
+    /// * `height` - Desired window height, before scaling.
     /// * `event_loop` - Event loop for handling window events.
+    /// * `window_config` - The placement of the window.
+    /// * `software` - Whether the software renderer is requested instead of the GPU.
     ///
     /// # Returns
     ///
     /// * Self - A new instance of `Backend`.
-    pub fn new(width: f32, height: f32, event_loop: winit::event_loop::EventLoop<Action>) -> Self {
+    pub fn new(
+        width: f32,
+        height: f32,
+        event_loop: &winit::event_loop::EventLoop<Action>,
+        window_config: &crate::config::WindowConfig,
+        software: bool,
+    ) -> Self {
+        // Selects the monitor and the scale of the overlay on it
+        let monitor = Backend::select_monitor(event_loop, window_config.monitor.as_ref());
+        let scale = window_config
+            .scale
+            .unwrap_or_else(|| monitor.as_ref().map_or(1.0, Backend::auto_scale));
+        // The window size is given in the pixels of a 1080p monitor without DPI scaling
+        let pixels_per_unit = scale as f64 * monitor.as_ref().map_or(1.0, |m| m.scale_factor());
+        log::info!(
+            "Overlay scale: {:.2} ({:.2} px per unit)",
+            scale,
+            pixels_per_unit
+        );
+
         // Creates a new window with specific configurations
         let window = winit::window::WindowBuilder::new()
             .with_decorations(false)
             .with_resizable(false)
             .with_transparent(true)
             .with_always_on_top(true)
-            .with_inner_size(winit::dpi::PhysicalSize { width, height })
-            .build(&event_loop)
+            .with_inner_size(winit::dpi::PhysicalSize::new(
+                (width as f64 * pixels_per_unit) as u32,
+                (height as f64 * pixels_per_unit) as u32,
+            ))
+            .build(event_loop)
             .unwrap();
 
-        // Positions the window at the top-right of the screen
-        window.set_outer_position(winit::dpi::PhysicalPosition::new(
-            window.current_monitor().unwrap().size().width - window.inner_size().width - 30,
-            30,
-        ));
-
-        // Instance creation for WGPU
-        let instance = wgpu::Instance::new(wgpu::Backends::PRIMARY);
-        let surface = unsafe { instance.create_surface(&window) };
-
-        // Request for a compatible adapter
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        }))
-        .unwrap();
-
-        // Request for a device and a command queue
-        let (device, queue) = pollster::block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                features: wgpu::Features::default(),
-                limits: wgpu::Limits::default(),
-                label: None,
-            },
-            None,
-        ))
-        .unwrap();
-
-        // Surface configuration
+        // Positions the window on the monitor
+        match &monitor {
+            Some(monitor) => {
+                let origin = monitor.position();
+                // The saved position, or the position given by the anchor
+                let [x, y] = window_config.position.unwrap_or_else(|| {
+                    Backend::anchored_position(
+                        window_config.anchor,
+                        window_config.offset,
+                        pixels_per_unit,
+                        monitor.size(),
+                        window.outer_size(),
+                    )
+                });
+                window.set_outer_position(winit::dpi::PhysicalPosition::new(
+                    origin.x + x,
+                    origin.y + y,
+                ));
+            }
+            None => log::error!("No monitor found, the window is left at its default position"),
+        }
+
+        // Renderer setup, on the GPU if possible
+        let renderer = super::renderer::Renderer::new(&window, software);
         let size = window.inner_size();
-        let surface_format = surface.get_supported_formats(&adapter)[0];
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
-        };
-        surface.configure(&device, &surface_config);
-
-        // Adding a custom font to `egui`
-        let mut custom_fonts = egui::FontDefinitions::default();
-        let font_data = include_bytes!("fonts/Monospac821 WGL4 BT Bold.ttf");
-        custom_fonts.font_data.insert(
-            "custom_monospace".to_owned(),
-            egui::FontData::from_static(font_data),
-        );
-        custom_fonts.families.insert(
-            egui::FontFamily::Name("MonospaceX".into()),
-            vec!["custom_monospace".to_owned()],
-        );
 
         // Platform setup for egui
         let platform =
@@ -121,35 +116,197 @@ impl Backend {
                 physical_width: size.width,
                 physical_height: size.height,
                 scale_factor: window.scale_factor(),
-                font_definitions: custom_fonts,
+                font_definitions: super::font_definitions(),
                 style: Default::default(),
             });
 
-        // RenderPass setup for `egui`
-        let egui_rpass = egui_wgpu_backend::RenderPass::new(&device, surface_format, 1);
+        let backend = Self {
+            window,
+            platform,
+            renderer,
+            scale,
+            window_platform: super::platform::current(),
+        };
 
         // Making the window transparent and unresponsive to mouse events
-        let hwnd = window.hwnd() as winapi::shared::windef::HWND;
-        unsafe {
-            let style = winapi::um::winuser::GetWindowLongA(hwnd, winapi::um::winuser::GWL_EXSTYLE);
-            winapi::um::winuser::SetWindowLongA(
-                hwnd,
-                winapi::um::winuser::GWL_EXSTYLE,
-                style
-                    | winapi::um::winuser::WS_EX_LAYERED as i32
-                    | winapi::um::winuser::WS_EX_TRANSPARENT as i32,
-            );
-        }
+        backend.window_platform.setup(&backend.window);
+        backend
+            .window_platform
+            .set_click_through(&backend.window, true);
 
-        Self {
-            window,
-            event_loop,
-            platform,
-            surface,
-            surface_config,
-            device,
-            queue,
-            egui_rpass,
+        backend
+    }
+
+    /// Turns the edit mode on or off. In the edit mode the window has a frame and reacts to
+    /// the mouse, so it can be moved and resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether the edit mode is on.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn set_edit_mode(&self, enabled: bool) {
+        self.window.set_decorations(enabled);
+        self.window.set_resizable(enabled);
+        self.window_platform
+            .set_click_through(&self.window, !enabled);
+    }
+
+    /// Tessellates the `egui` output and draws it into the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_output` - The output of the finished `egui` frame.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn render(&mut self, full_output: egui::FullOutput) {
+        let paint_jobs = self.platform.context().tessellate(full_output.shapes);
+        self.renderer.render(
+            &self.window,
+            self.window_platform.as_ref(),
+            &paint_jobs,
+            full_output.textures_delta,
+        );
+    }
+
+    /// Adapts the renderer after the window has been resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The new inner size of the window.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        self.renderer.resize(size);
+    }
+
+    /// Saves the current placement of the window in the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The window section of the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn save_placement(&self, config: &mut crate::config::WindowConfig) {
+        let monitor = self.window.current_monitor();
+        let origin = monitor
+            .as_ref()
+            .map(|monitor| monitor.position())
+            .unwrap_or_default();
+        config.position = self
+            .window
+            .outer_position()
+            .map(|position| [position.x - origin.x, position.y - origin.y])
+            .ok();
+        if let Some(name) = monitor.and_then(|monitor| monitor.name()) {
+            config.monitor = Some(crate::config::MonitorChoice::Name(name));
         }
+
+        // The size is saved in the same units as it is given
+        let pixels_per_unit = self.scale as f64 * self.window.scale_factor();
+        let size = self.window.inner_size();
+        config.size = Some([
+            (size.width as f64 / pixels_per_unit).round() as u32,
+            (size.height as f64 / pixels_per_unit).round() as u32,
+        ]);
+    }
+
+    /// Selects the monitor by its index or name.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_loop` - Event loop which knows the monitors.
+    /// * `choice` - The monitor from the configuration, if any.
+    ///
+    /// # Returns
+    ///
+    /// * The chosen monitor, the primary one if it is not found, or None if there are no monitors.
+    fn select_monitor(
+        event_loop: &winit::event_loop::EventLoop<Action>,
+        choice: Option<&crate::config::MonitorChoice>,
+    ) -> Option<winit::monitor::MonitorHandle> {
+        let chosen = choice.and_then(|choice| {
+            let monitor = match choice {
+                crate::config::MonitorChoice::Index(index) => {
+                    event_loop.available_monitors().nth(*index)
+                }
+                crate::config::MonitorChoice::Name(name) => event_loop
+                    .available_monitors()
+                    .find(|monitor| monitor.name().as_ref() == Some(name)),
+            };
+            if monitor.is_none() {
+                log::error!("Monitor {:?} not found, using the primary one", choice);
+            }
+            monitor
+        });
+
+        chosen
+            .or_else(|| event_loop.primary_monitor())
+            .or_else(|| event_loop.available_monitors().next())
+    }
+
+    /// Chooses the scale of the overlay, so it looks the same as on a 1080p monitor.
+    /// The DPI scaling of the monitor is applied separately by `egui`.
+    ///
+    /// # Arguments
+    ///
+    /// * `monitor` - The monitor the overlay is placed on.
+    ///
+    /// # Returns
+    ///
+    /// * The scale factor.
+    fn auto_scale(monitor: &winit::monitor::MonitorHandle) -> f32 {
+        let logical_height = monitor.size().height as f64 / monitor.scale_factor();
+        (logical_height / 1080.0).clamp(0.5, 4.0) as f32
+    }
+
+    /// Calculates the position of the window attached to the anchor of the monitor.
+    ///
+    /// # Arguments
+    ///
+    /// * `anchor` - The corner or side of the monitor.
+    /// * `offset` - The offset from the anchor, before scaling.
+    /// * `pixels_per_unit` - The scale of the offset.
+    /// * `monitor_size` - The size of the monitor.
+    /// * `window_size` - The outer size of the window.
+    ///
+    /// # Returns
+    ///
+    /// * The position relative to the top left corner of the monitor.
+    fn anchored_position(
+        anchor: crate::config::Anchor,
+        offset: [i32; 2],
+        pixels_per_unit: f64,
+        monitor_size: winit::dpi::PhysicalSize<u32>,
+        window_size: winit::dpi::PhysicalSize<u32>,
+    ) -> [i32; 2] {
+        let align = egui::Align2::from(anchor);
+        let place = |align: egui::Align, monitor: u32, window: u32, offset: i32| {
+            let free = monitor as i32 - window as i32;
+            let position = match align {
+                egui::Align::Min => 0,
+                egui::Align::Center => free / 2,
+                egui::Align::Max => free,
+            };
+            position + (offset as f64 * pixels_per_unit) as i32
+        };
+
+        [
+            place(align.x(), monitor_size.width, window_size.width, offset[0]),
+            place(
+                align.y(),
+                monitor_size.height,
+                window_size.height,
+                offset[1],
+            ),
+        ]
     }
 }
@@ -19,38 +19,64 @@
 // SOFTWARE.
 
 //! `Log` is one of the widgets (blocks) of the `Overlay` component. It creates a scrolling area
-//! where incoming events are displayed with a timer.
+//! where incoming events are displayed with a timer. New events slide in, expiring events fade
+//! out, and repeated identical events are shown once with a counter. The deaths are filtered by
+//! the settings of the kill feed.
 
-use super::super::events::Event;
+use super::super::events::{Action, CreatureKills, Event, PlayerDead};
+use super::super::theme::Theme;
+use crate::config::{KillCauser, KillFeedConfig, LogConfig};
 use crate::objects::Game;
 use crate::state::StateHolder;
 
+use std::collections::VecDeque;
+
+/// The `Entry` struct is an event in the log.
+#[derive(Debug)]
+struct Entry {
+    /// The latest of the grouped events.
+    event: Box<dyn Event>,
+    /// The number of grouped events.
+    count: usize,
+    /// The time of `egui` when the entry was shown first, None if it is not shown yet.
+    shown: Option<f64>,
+}
+
 /// The `Log` struct represents a log widget, maintaining a queue of events.
 pub struct Log {
     /// Queue of events to be logged.
-    log: std::collections::VecDeque<Box<dyn Event>>,
+    log: VecDeque<Entry>,
+    /// The capacity and the animations of the log.
+    config: LogConfig,
+    /// The filters of the deaths.
+    kill_feed: KillFeedConfig,
 }
 
-/// The `Default` implementation provides the initial state for the `Log` widget.
 impl Default for Log {
+    /// Provides the initial state for the `Log` widget with the default settings.
     fn default() -> Self {
-        Self {
-            log: std::collections::VecDeque::new(),
-        }
+        Log::new(&LogConfig::default(), &KillFeedConfig::default())
     }
 }
 
-impl Log {
+impl super::Block for Log {
     /// This method renders the `Log` widget to the UI.
     ///
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    pub fn show(&mut self, ui: &mut egui::Ui) {
+    fn show(&mut self, ui: &mut egui::Ui, theme: &Theme) {
+        // Expired events are not shown anymore
+        self.log.retain(|entry| !entry.event.remaining().is_zero());
+
+        let time = ui.input().time;
+        let mut animating = false;
+
         // Create a vertical ScrollArea that automatically shrinks and sticks to the bottom
         egui::Frame::none().show(ui, |ui| {
             egui::ScrollArea::vertical()
@@ -58,20 +84,41 @@ impl Log {
                 .stick_to_bottom(true)
                 .show(ui, |ui| {
                     // Display each event in the log
-                    for event in self.log.iter_mut() {
-                        event.show(ui);
+                    for entry in self.log.iter_mut() {
+                        let shown = *entry.shown.get_or_insert(time);
+                        let slide = Log::progress((time - shown) as f32, self.config.slide);
+                        let fade = Log::progress(
+                            entry.event.remaining().num_milliseconds() as f32 / 1000.0,
+                            self.config.fade,
+                        );
+                        animating |= slide < 1.0 || fade < 1.0;
+
+                        let theme = match slide.min(fade) {
+                            opacity if opacity < 1.0 => theme.faded(opacity),
+                            _ => theme.clone(),
+                        };
+                        ui.horizontal(|ui| {
+                            // New events slide in from the side
+                            ui.add_space((1.0 - slide) * theme.spacing * 4.0);
+                            entry.event.show(ui, &theme);
+                            if entry.count > 1 {
+                                super::super::show_label(
+                                    ui,
+                                    format!("x{}", entry.count),
+                                    theme.colors.event,
+                                    egui::FontFamily::Name("MonospaceX".into()),
+                                    theme.font_sizes.event,
+                                );
+                            }
+                        });
                     }
                 });
         });
-    }
 
-    /// This method adds an event to the log.
-    ///
-    /// # Arguments
-    ///
-    /// * `event` - A `Box` containing an object implementing the `Event` trait.
-    pub fn post(&mut self, event: Box<dyn Event>) {
-        self.log.push_back(event)
+        // Animations need the following frames as soon as possible
+        if animating {
+            ui.ctx().request_repaint();
+        }
     }
 
     /// This method updates the state of the `Log` widget based on the game state.
@@ -84,9 +131,260 @@ impl Log {
     /// # Returns
     ///
     /// * None
-    pub fn on_state_update(&mut self, game: Option<Game>, _state: std::sync::Arc<StateHolder>) {
+    fn on_state_update(&mut self, game: Option<Game>, _state: std::sync::Arc<StateHolder>) {
         if game.is_none() {
             self.log.clear();
         }
     }
+
+    /// This method updates the logged events and posts the events sent by the parsers to the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_action(&mut self, action: &Action) {
+        // The deaths hidden by the filters don't update the logged events either
+        if let Action::PlayerDead(death) = action {
+            if !self.shows_death(death) {
+                return;
+            }
+        }
+
+        for entry in self.log.iter_mut() {
+            entry.event.on_action(action);
+        }
+
+        // The kills by creatures are added to the summary above, or start a new one
+        if let Action::PlayerDead(death) = action {
+            if self.kill_feed.collapse_ai && death.causer() == KillCauser::Creature {
+                let summarized = self.log.iter().any(|entry| {
+                    entry.event.group_key().as_deref() == Some(CreatureKills::GROUP_KEY)
+                        && !entry.event.remaining().is_zero()
+                });
+                if !summarized {
+                    self.post(Box::new(CreatureKills::new(death)));
+                }
+                return;
+            }
+        }
+        if let Some(event) = action.event() {
+            self.post(event);
+        }
+    }
+}
+
+impl Log {
+    /// Creates a new `Log` widget.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The capacity and the animations of the log.
+    /// * `kill_feed` - The filters of the deaths.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Log`.
+    pub fn new(config: &LogConfig, kill_feed: &KillFeedConfig) -> Self {
+        let mut kill_feed = kill_feed.clone();
+        // Without the text of the own deaths no death is taken as the player's, so the filter
+        // would hide all of them
+        if kill_feed.only_mine && kill_feed.own_death.is_none() {
+            log::warn!("`only_mine` of the kill feed needs `own_death`, showing all deaths");
+            kill_feed.only_mine = false;
+        }
+
+        Self {
+            log: VecDeque::new(),
+            config: config.clone(),
+            kill_feed,
+        }
+    }
+
+    /// Checks the death against the filters of the kill feed.
+    ///
+    /// # Arguments
+    ///
+    /// * `death` - The death sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the death is shown.
+    fn shows_death(&self, death: &PlayerDead) -> bool {
+        if self.kill_feed.only_mine && !death.own() {
+            return false;
+        }
+        if !self.kill_feed.causers.contains(&death.causer()) {
+            return false;
+        }
+        // The deaths without a weapon, e.g. by creatures, have no rarity to compare
+        match (&self.kill_feed.min_rarity, death.weapon_rarity()) {
+            (Some(min_rarity), Some(rarity)) => rarity >= min_rarity,
+            _ => true,
+        }
+    }
+
+    /// This method adds an event to the log. A repeated identical event replaces the previous
+    /// one and increases its counter.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - A `Box` containing an object implementing the `Event` trait.
+    pub fn post(&mut self, event: Box<dyn Event>) {
+        let key = event.group_key().filter(|_| self.config.group);
+        let repeated = key.and_then(|key| {
+            self.log.iter_mut().find(|entry| {
+                entry.event.group_key().as_ref() == Some(&key) && !entry.event.remaining().is_zero()
+            })
+        });
+
+        match repeated {
+            Some(entry) => {
+                entry.event = event;
+                entry.count += 1;
+            }
+            None => {
+                self.log.push_back(Entry {
+                    event,
+                    count: 1,
+                    shown: None,
+                });
+                // The oldest events make room for the new ones
+                while self.log.len() > self.config.capacity.max(1) {
+                    self.log.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Calculates the progress of an animation.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - The seconds since the beginning of the animation, or until its end.
+    /// * `duration` - The duration of the animation in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * The progress from 0 to 1, which is 1 if the animation is disabled.
+    fn progress(elapsed: f32, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 1.0;
+        }
+        (elapsed / duration).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::events::PlayerEscaped;
+    use super::*;
+    use crate::objects::{Actor, Rarity, Weapon};
+
+    /// Provides an escape which is still shown, or one which has expired.
+    fn escape(message: &str, live: bool) -> Box<dyn Event> {
+        let time = if live {
+            chrono::Utc::now()
+        } else {
+            chrono::Utc::now() - chrono::Duration::hours(1)
+        };
+        Box::new(PlayerEscaped::new(
+            time,
+            chrono::Duration::seconds(60),
+            message.to_string(),
+            true,
+        ))
+    }
+
+    fn death(actor: &str, weapon: Option<&str>, own: bool) -> PlayerDead {
+        PlayerDead::new(
+            chrono::Utc::now(),
+            chrono::Duration::seconds(15),
+            Actor::get(actor.to_string()),
+            0,
+            weapon.and_then(Weapon::get),
+            50.0,
+            own,
+        )
+    }
+
+    fn texts(log: &Log) -> Vec<String> {
+        log.log.iter().map(|entry| entry.event.text()).collect()
+    }
+
+    #[test]
+    fn oldest_events_are_evicted_first() {
+        let config = LogConfig {
+            capacity: 2,
+            ..LogConfig::default()
+        };
+        let mut log = Log::new(&config, &KillFeedConfig::default());
+        for message in ["first", "second", "third"] {
+            log.post(escape(message, true));
+        }
+        assert_eq!(texts(&log), vec!["second", "third"]);
+    }
+
+    #[test]
+    fn repeated_events_are_grouped_while_live() {
+        let mut log = Log::default();
+        log.post(escape("escaped", true));
+        log.post(escape("escaped", true));
+        assert_eq!(log.log.len(), 1);
+        assert_eq!(log.log[0].count, 2);
+
+        // An expired event starts a new entry
+        let mut log = Log::default();
+        log.post(escape("escaped", false));
+        log.post(escape("escaped", true));
+        assert_eq!(log.log.len(), 2);
+        assert_eq!(log.log[1].count, 1);
+    }
+
+    #[test]
+    fn zero_durations_disable_the_animations() {
+        assert_eq!(Log::progress(0.0, 0.0), 1.0);
+        assert_eq!(Log::progress(0.5, -1.0), 1.0);
+        assert_eq!(Log::progress(0.5, 1.0), 0.5);
+        assert_eq!(Log::progress(2.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn deaths_are_filtered_by_rarity_and_causer() {
+        let kill_feed = KillFeedConfig {
+            causers: vec![KillCauser::Player],
+            min_rarity: Some(Rarity::Rare),
+            ..KillFeedConfig::default()
+        };
+        let log = Log::new(&LogConfig::default(), &kill_feed);
+        let player = "PRO_PlayerCharacter";
+        assert!(log.shows_death(&death(player, Some("WP_D_BR_Shard_01"), false)));
+        assert!(!log.shows_death(&death(player, Some("WP_E_Pistol_Bullet_01"), false)));
+        // A death without a weapon has no rarity to compare
+        assert!(log.shows_death(&death(player, None, false)));
+        assert!(!log.shows_death(&death("AIChar_Crusher_BP", None, false)));
+    }
+
+    #[test]
+    fn only_own_deaths_are_shown_with_own_death() {
+        let kill_feed = KillFeedConfig {
+            only_mine: true,
+            own_death: Some("YourName".to_string()),
+            ..KillFeedConfig::default()
+        };
+        let log = Log::new(&LogConfig::default(), &kill_feed);
+        assert!(log.shows_death(&death("AIChar_Crusher_BP", None, true)));
+        assert!(!log.shows_death(&death("AIChar_Crusher_BP", None, false)));
+
+        // Without the text of the own deaths the filter is turned off
+        let kill_feed = KillFeedConfig {
+            own_death: None,
+            ..kill_feed
+        };
+        let log = Log::new(&LogConfig::default(), &kill_feed);
+        assert!(log.shows_death(&death("AIChar_Crusher_BP", None, false)));
+    }
 }
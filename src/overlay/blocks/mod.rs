@@ -18,8 +18,118 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-//! This module contains modules related to `Overlay` widgets (blocks)
+//! This module contains modules related to `Overlay` widgets (blocks) and the registry which
+//! creates them by their names in the layout.
 
 pub mod log;
+pub mod recap;
+mod series;
 pub mod server;
+pub mod summary;
 pub mod time;
+
+use super::events::Action;
+use super::theme::Theme;
+use crate::audio::Audio;
+use crate::config::Config;
+use crate::objects::Game;
+use crate::state::StateHolder;
+
+use std::collections::HashMap;
+
+/// The `Block` trait defines an interface for all widgets (blocks) of the `Overlay`.
+pub trait Block {
+    /// Renders the widget to the UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    fn show(&mut self, ui: &mut egui::Ui, theme: &Theme);
+
+    /// Updates the widget based on the game state.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - An Option that can contain the current game state.
+    /// * `state` - A reference to the `StateHolder` which holds the state information.
+    fn on_state_update(&mut self, game: Option<Game>, state: std::sync::Arc<StateHolder>);
+
+    /// Handles an action sent by the parsers. The widgets ignore actions by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action, except state updates which are passed to `on_state_update`.
+    fn on_action(&mut self, _action: &Action) {}
+
+    /// Runs the work of the widget which doesn't depend on drawing, e.g. the alerts of the
+    /// countdowns. It is called once a second, even if the widget is not shown.
+    fn tick(&mut self) {}
+}
+
+/// The function that creates a widget from the configuration and the audio service.
+type Constructor = fn(&Config, &Audio) -> Box<dyn Block>;
+
+/// The `Registry` struct creates widgets (blocks) by their names.
+pub struct Registry {
+    /// Constructors of the widgets by their names.
+    constructors: HashMap<&'static str, Constructor>,
+}
+
+impl Registry {
+    /// Registers a widget under the name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the widget in the layout.
+    /// * `constructor` - The function that creates the widget from the configuration and the
+    ///   audio service.
+    pub fn register(&mut self, name: &'static str, constructor: Constructor) {
+        self.constructors.insert(name, constructor);
+    }
+
+    /// Creates the widgets listed in the layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the widgets in their order.
+    /// * `config` - The user configuration with the settings of the widgets.
+    /// * `audio` - The audio service which plays the alerts of the widgets.
+    ///
+    /// # Returns
+    ///
+    /// * The created widgets in the same order, unknown names are reported and skipped.
+    pub fn build(&self, names: &[String], config: &Config, audio: &Audio) -> Vec<Box<dyn Block>> {
+        names
+            .iter()
+            .filter_map(|name| match self.constructors.get(name.as_str()) {
+                Some(constructor) => Some(constructor(config, audio)),
+                None => {
+                    ::log::error!("Unknown overlay block: {:?}", name);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for Registry {
+    /// Provides the registry with all built-in widgets.
+    fn default() -> Self {
+        let mut registry = Self {
+            constructors: HashMap::new(),
+        };
+        registry.register("server", |config, _| {
+            Box::new(server::Server::new(&config.proximity))
+        });
+        registry.register("time", |config, audio| {
+            Box::new(time::Time::new(&config.time, audio.clone()))
+        });
+        registry.register("log", |config, _| {
+            Box::new(log::Log::new(&config.log, &config.kill_feed))
+        });
+        registry.register("recap", |_, _| Box::<recap::Recap>::default());
+        registry.register("summary", |_, _| Box::<summary::Summary>::default());
+        registry
+    }
+}
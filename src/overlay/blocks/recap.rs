@@ -0,0 +1,90 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! `Recap` is one of the widgets (blocks) of the `Overlay` component. It shows the recap of the
+//! death of the player until it is dismissed or the player returns to the lobby.
+
+use super::super::events::{Action, DeathRecap};
+use super::super::theme::Theme;
+use crate::objects::Game;
+use crate::state::StateHolder;
+
+/// The `Recap` struct represents the death recap widget.
+#[derive(Default)]
+pub struct Recap {
+    /// The recap of the latest death, None if there is nothing to show.
+    recap: Option<DeathRecap>,
+}
+
+impl super::Block for Recap {
+    /// This method renders the `Recap` widget to the UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn show(&mut self, ui: &mut egui::Ui, theme: &Theme) {
+        if let Some(recap) = self.recap.as_ref() {
+            recap.show(ui, theme);
+        }
+    }
+
+    /// This method drops the recap when the player returns to the lobby or joins another game.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - An Option that can contain the current game state.
+    /// * `_state` - A reference to the `StateHolder` which holds the state information.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_state_update(&mut self, game: Option<Game>, _state: std::sync::Arc<StateHolder>) {
+        // The state of the same game is also updated, e.g. by the clock corrections
+        let same_game = match (game.as_ref(), self.recap.as_ref()) {
+            (Some(game), Some(recap)) => game.instance_id.to_string() == recap.instance_id(),
+            _ => false,
+        };
+        if !same_game {
+            self.recap = None;
+        }
+    }
+
+    /// This method shows a new recap or dismisses the current one.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_action(&mut self, action: &Action) {
+        match action {
+            Action::DeathRecap(recap) => self.recap = Some(recap.clone()),
+            Action::DismissRecap => self.recap = None,
+            _ => (),
+        }
+    }
+}
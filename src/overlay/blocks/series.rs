@@ -0,0 +1,235 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the time series of a value shown by the widgets, e.g. the number of
+//! players, with its statistics and a sparkline.
+
+/// The period the trend of a series is calculated over in seconds.
+const TREND_PERIOD: i64 = 120;
+
+/// Enum representing the direction a series has moved in recently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    /// Returns the arrow of the trend.
+    ///
+    /// # Returns
+    ///
+    /// * The arrow as a string.
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Trend::Rising => "↑",
+            Trend::Falling => "↓",
+            Trend::Steady => "→",
+        }
+    }
+}
+
+/// The `Series` struct keeps the values of a series with the times of their changes.
+#[derive(Debug, Clone, Default)]
+pub struct Series {
+    /// The values with their times, the oldest first.
+    points: Vec<(chrono::DateTime<chrono::Utc>, usize)>,
+}
+
+impl Series {
+    /// Adds a value to the series.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the change.
+    /// * `value` - The new value.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn push(&mut self, time: chrono::DateTime<chrono::Utc>, value: usize) {
+        // The changes of the same moment are merged into the last value
+        match self.points.last_mut() {
+            Some(last) if last.0 >= time => last.1 = value,
+            _ => self.points.push((time, value)),
+        }
+    }
+
+    /// Drops all values of the series.
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Returns the highest value of the series.
+    pub fn peak(&self) -> Option<usize> {
+        self.points.iter().map(|(_, value)| *value).max()
+    }
+
+    /// Returns the lowest value of the series.
+    pub fn min(&self) -> Option<usize> {
+        self.points.iter().map(|(_, value)| *value).min()
+    }
+
+    /// Returns the value of the series at the moment.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The moment.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the latest value before the moment, or None if the series begins later.
+    fn value_at(&self, time: chrono::DateTime<chrono::Utc>) -> Option<usize> {
+        let index = self.points.partition_point(|(point, _)| *point <= time);
+        index.checked_sub(1).map(|index| self.points[index].1)
+    }
+
+    /// Returns the direction the series has moved in during the recent period.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    ///
+    /// * The trend of the series, steady if it is too short.
+    pub fn trend(&self, now: chrono::DateTime<chrono::Utc>) -> Trend {
+        let before = now - chrono::Duration::seconds(TREND_PERIOD);
+        // A series which begins within the period is compared with its first value
+        let past = self
+            .value_at(before)
+            .or_else(|| self.points.first().map(|(_, value)| *value));
+        match (past, self.value_at(now)) {
+            (Some(past), Some(current)) if current > past => Trend::Rising,
+            (Some(past), Some(current)) if current < past => Trend::Falling,
+            _ => Trend::Steady,
+        }
+    }
+
+    /// Draws the series as a step line from its beginning to the current time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `size` - The size of the sparkline.
+    /// * `color` - The color of the line.
+    /// * `now` - The current time, the right edge of the sparkline.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn sparkline(
+        &self,
+        ui: &mut egui::Ui,
+        size: egui::Vec2,
+        color: egui::Color32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) {
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let (start, min, peak) = match (self.points.first(), self.min(), self.peak()) {
+            (Some((start, _)), Some(min), Some(peak)) => (*start, min, peak),
+            _ => return,
+        };
+
+        // Map the times and values into the rectangle, the minimum is at the bottom
+        let span = (now - start).num_milliseconds().max(1) as f32;
+        let x = |time: chrono::DateTime<chrono::Utc>| {
+            let offset = (time - start).num_milliseconds().clamp(0, span as i64) as f32;
+            rect.left() + rect.width() * offset / span
+        };
+        let range = (peak - min).max(1) as f32;
+        let y = |value: usize| rect.bottom() - rect.height() * (value - min) as f32 / range;
+
+        let mut line: Vec<egui::Pos2> = Vec::with_capacity(self.points.len() * 2 + 1);
+        for (time, value) in self.points.iter() {
+            if let Some(last) = line.last().copied() {
+                line.push(egui::pos2(x(*time), last.y));
+            }
+            line.push(egui::pos2(x(*time), y(*value)));
+        }
+        if let Some(last) = line.last().copied() {
+            line.push(egui::pos2(rect.right(), last.y));
+        }
+        ui.painter()
+            .add(egui::Shape::line(line, egui::Stroke::new(1.5, color)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a series from values with their seconds before `now`.
+    fn series(now: chrono::DateTime<chrono::Utc>, points: &[(i64, usize)]) -> Series {
+        let mut series = Series::default();
+        for (ago, value) in points {
+            series.push(now - chrono::Duration::seconds(*ago), *value);
+        }
+        series
+    }
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    #[test]
+    fn empty_and_single_value_series_are_steady() {
+        assert_eq!(Series::default().trend(now()), Trend::Steady);
+        assert_eq!(series(now(), &[(10, 5)]).trend(now()), Trend::Steady);
+    }
+
+    #[test]
+    fn short_series_is_compared_with_its_first_value() {
+        assert_eq!(
+            series(now(), &[(30, 5), (10, 7)]).trend(now()),
+            Trend::Rising
+        );
+        assert_eq!(
+            series(now(), &[(30, 5), (10, 3)]).trend(now()),
+            Trend::Falling
+        );
+    }
+
+    #[test]
+    fn trend_covers_only_the_recent_period() {
+        // The fall happened before the period, since then the value has not changed
+        let steady = series(now(), &[(600, 10), (300, 4), (30, 4)]);
+        assert_eq!(steady.trend(now()), Trend::Steady);
+        // The value before the period is the one the recent values are compared with
+        let rising = series(now(), &[(600, 10), (300, 4), (30, 6)]);
+        assert_eq!(rising.trend(now()), Trend::Rising);
+    }
+
+    #[test]
+    fn future_values_are_ignored() {
+        assert_eq!(
+            series(now(), &[(60, 5), (-10, 9)]).trend(now()),
+            Trend::Steady
+        );
+    }
+
+    #[test]
+    fn changes_of_the_same_moment_are_merged() {
+        let series = series(now(), &[(10, 5), (10, 8), (10, 2)]);
+        assert_eq!(series.points.len(), 1);
+        assert_eq!((series.min(), series.peak()), (Some(2), Some(2)));
+    }
+}
@@ -21,7 +21,11 @@
 //! `Server` is one of the widgets (blocks) of the `Overlay` component.
 //! It creates several labels where it displays server information and the number of players.
 
-use crate::objects::Game;
+use super::super::events::Action;
+use super::super::theme::Theme;
+use super::series::Series;
+use crate::config::ProximityConfig;
+use crate::objects::{Game, MeteorShower};
 use crate::state::StateHolder;
 
 /// The `Server` struct represents a server widget, maintaining server and player info.
@@ -32,12 +36,34 @@ pub struct Server {
     pub near_players: usize,
     /// The number of party size.
     pub party_size: usize,
+    /// The mode of the match.
+    pub mode: Option<String>,
     /// Name of the current game session.
     pub session_name: String,
+    /// Short region code and instance ID of the current game session.
+    pub session_id: String,
+    /// Warning about data verified against an older build of the game.
+    pub outdated: Option<String>,
+    /// The number of meteor showers on the server.
+    meteors: usize,
+    /// The latest meteor shower on the server.
+    last_meteors: Option<MeteorShower>,
+    /// The total number of players over the current match.
+    total_series: Series,
+    /// The number of players near the user over the current match.
+    near_series: Series,
+    /// Whether the number of nearby players flashes with the proximity alerts.
+    flash_near: bool,
+    /// The flashing of the number of nearby players: Some(None) until it is shown first, then
+    /// the time of `egui` when it stops.
+    near_flash: Option<Option<f64>>,
     /// Visibility of the widget.
     pub visible: bool,
 }
 
+/// The duration of the flashing of the number of nearby players in seconds.
+const NEAR_FLASH_SECONDS: f64 = 3.0;
+
 /// The `Default` implementation provides the initial state for the `Server` widget.
 impl Default for Server {
     fn default() -> Self {
@@ -45,26 +71,81 @@ impl Default for Server {
             total_players: 0,
             near_players: 0,
             party_size: 0,
+            mode: None,
             session_name: String::new(),
+            session_id: String::new(),
+            outdated: None,
+            meteors: 0,
+            last_meteors: None,
+            total_series: Series::default(),
+            near_series: Series::default(),
+            flash_near: true,
+            near_flash: None,
             visible: false,
         }
     }
 }
 
 impl Server {
-    /// Constant defining the orange color used in the widget
-    const ORANGE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 128, 0);
+    /// Creates a new `Server` widget.
+    ///
+    /// # Arguments
+    ///
+    /// * `proximity` - The rules of the proximity alerts.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Server`.
+    pub fn new(proximity: &ProximityConfig) -> Self {
+        Self {
+            flash_near: proximity.flash,
+            ..Default::default()
+        }
+    }
 
+    /// Returns the color of the number of nearby players, which pulses after a proximity alert.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * The color of the label.
+    fn near_color(&mut self, ui: &egui::Ui, theme: &Theme) -> egui::Color32 {
+        let time = ui.input().time;
+        let until = match self.near_flash.as_mut() {
+            Some(until) => *until.get_or_insert(time + NEAR_FLASH_SECONDS),
+            None => return theme.colors.server,
+        };
+        if time >= until {
+            self.near_flash = None;
+            return theme.colors.server;
+        }
+
+        ui.ctx().request_repaint();
+        // Switch between the warning and the usual color four times a second
+        if (time * 4.0) as i64 % 2 == 0 {
+            theme.colors.warning
+        } else {
+            theme.colors.server
+        }
+    }
+}
+
+impl super::Block for Server {
     /// This method renders the `Server` widget to the UI.
     ///
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    pub fn show(&mut self, ui: &mut egui::Ui) {
+    fn show(&mut self, ui: &mut egui::Ui, theme: &Theme) {
         // Check widget visibility
         if self.visible {
             // Frame for player information
@@ -75,31 +156,42 @@ impl Server {
                         super::super::show_label(
                             ui,
                             format!("PLAYERS: TOTAL {}", self.total_players),
-                            Server::ORANGE_COLOR,
+                            theme.colors.server,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            40.0,
+                            theme.font_sizes.players,
                         );
                         // Adding space for aesthetic purposes
-                        ui.add_space(20.0);
+                        ui.add_space(theme.spacing);
                         // Displaying delimiter
                         super::super::show_label(
                             ui,
                             "|".to_string(),
-                            Server::ORANGE_COLOR,
+                            theme.colors.server,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            40.0,
+                            theme.font_sizes.players,
                         );
                         // Adding space for aesthetic purposes
-                        ui.add_space(20.0);
+                        ui.add_space(theme.spacing);
                         // Displaying near players label
+                        let near_color = self.near_color(ui, theme);
                         super::super::show_label(
                             ui,
                             format!("NEAR {}", self.near_players),
-                            Server::ORANGE_COLOR,
+                            near_color,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            40.0,
+                            theme.font_sizes.players,
                         );
-                    })
+                    });
+                    // Displaying the population of the server over the match
+                    ui.horizontal(|ui| {
+                        let now = crate::utils::now();
+                        for (label, series) in
+                            [("TOTAL", &self.total_series), ("NEAR", &self.near_series)]
+                        {
+                            show_series(ui, theme, label, series, now);
+                            ui.add_space(theme.spacing);
+                        }
+                    });
                 })
             });
             // Frame for server information
@@ -109,24 +201,74 @@ impl Server {
                     super::super::show_label(
                         ui,
                         format!("SERVER: {}", self.session_name.clone()),
-                        Server::ORANGE_COLOR,
+                        theme.colors.server,
+                        egui::FontFamily::Name("MonospaceX".into()),
+                        theme.font_sizes.server,
+                    );
+                    // Displaying region and short instance ID of the session
+                    super::super::show_label(
+                        ui,
+                        self.session_id.clone(),
+                        theme.colors.server,
                         egui::FontFamily::Name("MonospaceX".into()),
-                        30.0,
+                        theme.font_sizes.detail,
                     );
                     if self.party_size > 1 {
                         // Adding space for aesthetic purposes
-                        ui.add_space(20.0);
+                        ui.add_space(theme.spacing);
                         // Displaying the size of party
                         super::super::show_label(
                             ui,
                             format!("PARTY: {:02}", self.party_size),
-                            Server::ORANGE_COLOR,
+                            theme.colors.server,
+                            egui::FontFamily::Name("MonospaceX".into()),
+                            theme.font_sizes.server,
+                        );
+                    }
+                    if let Some(mode) = &self.mode {
+                        // Adding space for aesthetic purposes
+                        ui.add_space(theme.spacing);
+                        // Displaying the mode of the match
+                        super::super::show_label(
+                            ui,
+                            format!("MODE: {}", mode),
+                            theme.colors.server,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            30.0,
+                            theme.font_sizes.server,
                         );
                     }
                 });
             });
+            if let Some(meteors) = &self.last_meteors {
+                // Displaying the meteor showers on the server with the phase of the latest one
+                let ago = (crate::utils::now() - meteors.time).num_minutes().max(0);
+                let phase = match (&meteors.phase, meteors.into_phase) {
+                    (Some(phase), Some(into)) => format!(
+                        " | {} +{}:{:02}",
+                        phase,
+                        into.num_minutes(),
+                        into.num_seconds() % 60
+                    ),
+                    _ => String::new(),
+                };
+                super::super::show_label(
+                    ui,
+                    format!("METEORS {} | LAST {}m AGO{}", self.meteors, ago, phase),
+                    theme.colors.server,
+                    egui::FontFamily::Name("MonospaceX".into()),
+                    theme.font_sizes.detail,
+                );
+            }
+            if let Some(outdated) = &self.outdated {
+                // Displaying the warning about outdated data
+                super::super::show_label(
+                    ui,
+                    outdated.clone(),
+                    theme.colors.warning,
+                    egui::FontFamily::Name("MonospaceX".into()),
+                    theme.font_sizes.detail,
+                );
+            }
         }
     }
 
@@ -140,11 +282,28 @@ impl Server {
     /// # Returns
     ///
     /// * None
-    pub fn on_state_update(&mut self, game: Option<Game>, state: std::sync::Arc<StateHolder>) {
+    fn on_state_update(&mut self, game: Option<Game>, state: std::sync::Arc<StateHolder>) {
         // If a game state is present
         if let Some(game) = game {
-            // Update party size
+            // Update party size and mode of the match
             self.party_size = game.party_size;
+            self.mode = game.options.mode.clone();
+            // Update region and short instance ID, a new match starts new series of players
+            let session_id = format!("[{:#} {}]", game.region, game.instance_id.short());
+            if session_id != self.session_id {
+                self.total_series.clear();
+                self.near_series.clear();
+            }
+            self.session_id = session_id;
+            // The meteor showers are kept by the game of the server
+            self.meteors = game.meteors().len();
+            self.last_meteors = game.meteors().last().cloned();
+            // Warn if the game build is newer than the data was verified against
+            self.outdated = game.build.as_ref().and_then(|build| {
+                let outdated = build.outdated_data(game.map.timings());
+                (!outdated.is_empty())
+                    .then(|| format!("GAME {} IS NEWER THAN: {}", build, outdated.join(", ")))
+            });
 
             // If the number of games ago is available
             if let Some(num_games) = state.games_ago() {
@@ -159,4 +318,80 @@ impl Server {
             self.visible = false;
         }
     }
+
+    /// This method updates the number of players based on the actions of the parsers.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_action(&mut self, action: &Action) {
+        match action {
+            Action::TotalPlayerCountUpdate(event) => {
+                self.total_players = event.players;
+                self.total_series.push(event.time, event.players);
+            }
+            Action::NearPlayerCountUpdate(event) => {
+                self.near_players = event.players;
+                self.near_series.push(event.time, event.players);
+            }
+            // The alerts of the log read from the beginning are not flashed
+            Action::ProximityAlert(event)
+                if self.flash_near
+                    && crate::utils::now() - event.time < chrono::Duration::seconds(10) =>
+            {
+                self.near_flash = Some(None);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Displays the sparkline of a series of players with its peak, minimum and trend.
+///
+/// # Arguments
+///
+/// * `ui` - A mutable reference to the `egui::Ui` instance.
+/// * `theme` - The colors and font sizes of the overlay.
+/// * `label` - The name of the series.
+/// * `series` - The number of players over the match.
+/// * `now` - The current time.
+///
+/// # Returns
+///
+/// * None
+fn show_series(
+    ui: &mut egui::Ui,
+    theme: &Theme,
+    label: &str,
+    series: &Series,
+    now: chrono::DateTime<chrono::Utc>,
+) {
+    let (peak, min) = match (series.peak(), series.min()) {
+        (Some(peak), Some(min)) => (peak, min),
+        _ => return,
+    };
+    let height = theme.font_sizes.detail;
+    series.sparkline(
+        ui,
+        egui::vec2(height * 6.0, height),
+        theme.colors.server,
+        now,
+    );
+    super::super::show_label(
+        ui,
+        format!(
+            "{} {} MIN {} PEAK {}",
+            label,
+            series.trend(now).arrow(),
+            min,
+            peak
+        ),
+        theme.colors.server,
+        egui::FontFamily::Name("MonospaceX".into()),
+        theme.font_sizes.detail,
+    );
 }
@@ -0,0 +1,442 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! `Summary` is one of the widgets (blocks) of the `Overlay` component. In the lobby it shows the
+//! summary of the last raid and the totals of all raids since the game was started.
+
+use super::super::events::Action;
+use super::super::theme::Theme;
+use crate::objects::Game;
+use crate::state::StateHolder;
+
+use std::collections::HashMap;
+
+/// Enum representing how a raid ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RaidResult {
+    /// The player escaped with the evac ship.
+    Escaped,
+    /// The player died.
+    Died,
+    /// The player left the raid without a result, e.g. after a disconnect.
+    Left,
+}
+
+/// The `Raid` struct collects the information about a raid while it is played.
+#[derive(Debug, Clone)]
+struct Raid {
+    /// The name of the map.
+    map: String,
+    /// The generated name of the server.
+    server: String,
+    /// The short region code and instance ID of the server.
+    session_id: String,
+    /// The moment the player joined the raid.
+    joined: chrono::DateTime<chrono::Utc>,
+    /// The time of the latest event of the raid.
+    last_seen: chrono::DateTime<chrono::Utc>,
+    /// The result of the raid, None while it is played.
+    result: Option<RaidResult>,
+    /// The highest number of players on the server.
+    peak_players: usize,
+    /// The number of times a player came near.
+    encountered: usize,
+    /// The current number of nearby players.
+    near_players: usize,
+    /// The number of events posted to the event log.
+    events: usize,
+    /// Boolean representing whether the raid was left once, so it counts in the totals.
+    finished: bool,
+}
+
+impl Raid {
+    /// Creates a new `Raid` from the joined game.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The joined game.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Raid`.
+    fn new(game: &Game) -> Self {
+        Self {
+            map: game.map.name().to_string(),
+            server: game.name.clone(),
+            session_id: format!("[{:#} {}]", game.region, game.instance_id.short()),
+            joined: game.joined,
+            last_seen: game.joined,
+            result: None,
+            peak_players: game.total_players,
+            encountered: 0,
+            near_players: game.near_players,
+            events: 0,
+            finished: false,
+        }
+    }
+}
+
+/// The `Totals` struct counts the raids since the game was started.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Totals {
+    /// The number of finished raids.
+    raids: usize,
+    /// The number of escapes.
+    escapes: usize,
+    /// The number of deaths.
+    deaths: usize,
+}
+
+/// The `Summary` struct represents the lobby summary widget.
+#[derive(Default)]
+pub struct Summary {
+    /// The raids since the game was started by their instance IDs, a rejoin continues its raid.
+    raids: HashMap<String, Raid>,
+    /// The instance ID of the current raid, or of the last one in the lobby.
+    current: Option<String>,
+    /// Boolean representing whether the widget is visible, i.e. the player is in the lobby.
+    visible: bool,
+}
+
+impl super::Block for Summary {
+    /// This method renders the `Summary` widget to the UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn show(&mut self, ui: &mut egui::Ui, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+        let raid = match self.raid() {
+            Some(raid) => raid,
+            None => return,
+        };
+        let totals = self.totals();
+
+        let font = egui::FontFamily::Name("MonospaceX".into());
+        egui::Frame::none().show(ui, |ui| {
+            ui.vertical(|ui| {
+                // The map and the server of the raid
+                ui.horizontal(|ui| {
+                    super::super::show_label(
+                        ui,
+                        format!("LAST RAID: {} | {}", raid.map, raid.server),
+                        theme.colors.server,
+                        font.clone(),
+                        theme.font_sizes.server,
+                    );
+                    super::super::show_label(
+                        ui,
+                        raid.session_id.clone(),
+                        theme.colors.server,
+                        font.clone(),
+                        theme.font_sizes.detail,
+                    );
+                });
+
+                // The result and the duration of the raid
+                let duration = (raid.last_seen - raid.joined).num_seconds().max(0);
+                let (result, color) = match raid.result.unwrap_or(RaidResult::Left) {
+                    RaidResult::Escaped => ("ESCAPED", theme.colors.event),
+                    RaidResult::Died => ("DIED", theme.colors.warning),
+                    RaidResult::Left => ("LEFT", theme.colors.event_inactive),
+                };
+                ui.horizontal(|ui| {
+                    super::super::show_label(
+                        ui,
+                        result.to_string(),
+                        color,
+                        font.clone(),
+                        theme.font_sizes.event,
+                    );
+                    super::super::show_label(
+                        ui,
+                        format!(
+                            "after {:02}:{:02} | players {} | met {} | events {}",
+                            duration / 60,
+                            duration % 60,
+                            raid.peak_players,
+                            raid.encountered,
+                            raid.events
+                        ),
+                        theme.colors.event,
+                        font.clone(),
+                        theme.font_sizes.event,
+                    );
+                });
+
+                // The totals of the session
+                super::super::show_label(
+                    ui,
+                    format!(
+                        "SESSION: {} raids | {} escapes | {} deaths",
+                        totals.raids, totals.escapes, totals.deaths
+                    ),
+                    theme.colors.timer,
+                    font.clone(),
+                    theme.font_sizes.detail,
+                );
+            });
+        });
+    }
+
+    /// This method starts a raid when a game is joined and shows its summary in the lobby.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - An Option that can contain the current game state.
+    /// * `_state` - A reference to the `StateHolder` which holds the state information.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_state_update(&mut self, game: Option<Game>, _state: std::sync::Arc<StateHolder>) {
+        match game {
+            Some(game) => {
+                // The state of the same game is also updated, e.g. by the clock corrections
+                let instance_id = game.instance_id.to_string();
+                if self.current.as_ref() != Some(&instance_id) {
+                    // The previous raid may end without the return to the lobby
+                    self.finish();
+                }
+                // A rejoin of the instance continues its raid
+                self.raids
+                    .entry(instance_id.clone())
+                    .or_insert_with(|| Raid::new(&game));
+                self.current = Some(instance_id);
+                self.visible = false;
+            }
+            None => {
+                self.finish();
+                self.visible = true;
+            }
+        }
+    }
+
+    /// This method collects the players and events of the current raid.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_action(&mut self, action: &Action) {
+        // The last raid is finished in the lobby
+        if self.visible {
+            return;
+        }
+        let raid = match self
+            .current
+            .as_ref()
+            .and_then(|instance_id| self.raids.get_mut(instance_id))
+        {
+            Some(raid) => raid,
+            None => return,
+        };
+
+        match action {
+            // The changes of the players are not shown, but tell that the raid still goes on
+            Action::TotalPlayerCountUpdate(event) => {
+                raid.peak_players = raid.peak_players.max(event.players);
+                raid.last_seen = raid.last_seen.max(event.time);
+            }
+            Action::NearPlayerCountUpdate(event) => {
+                // Each increase of the nearby players is a player coming near
+                raid.encountered += event.players.saturating_sub(raid.near_players);
+                raid.near_players = event.players;
+                raid.last_seen = raid.last_seen.max(event.time);
+            }
+            // The first result is kept, and only the escapes and deaths of the player count
+            Action::PlayerEscaped(event) if event.own() => {
+                raid.result.get_or_insert(RaidResult::Escaped);
+            }
+            Action::PlayerDead(event) if event.own() => {
+                raid.result.get_or_insert(RaidResult::Died);
+            }
+            _ => (),
+        }
+        if let Some(event) = action.event() {
+            raid.events += 1;
+            raid.last_seen = raid.last_seen.max(event.time());
+        }
+    }
+}
+
+impl Summary {
+    /// Provides the current raid, or the last one in the lobby.
+    ///
+    /// # Returns
+    ///
+    /// * The raid, None if no game was joined yet.
+    fn raid(&self) -> Option<&Raid> {
+        self.current
+            .as_ref()
+            .and_then(|instance_id| self.raids.get(instance_id))
+    }
+
+    /// Marks the current raid as finished, so it counts in the totals.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn finish(&mut self) {
+        if let Some(raid) = self
+            .current
+            .as_ref()
+            .and_then(|instance_id| self.raids.get_mut(instance_id))
+        {
+            raid.finished = true;
+        }
+    }
+
+    /// Counts the finished raids by their instances, so a rejoined raid is counted once.
+    ///
+    /// # Returns
+    ///
+    /// * The totals of the finished raids.
+    fn totals(&self) -> Totals {
+        let mut totals = Totals::default();
+        for raid in self.raids.values().filter(|raid| raid.finished) {
+            totals.raids += 1;
+            match raid.result {
+                Some(RaidResult::Escaped) => totals.escapes += 1,
+                Some(RaidResult::Died) => totals.deaths += 1,
+                _ => (),
+            }
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::events::{self, Action};
+    use super::super::Block;
+    use super::*;
+    use crate::objects::{GameMap, InstanceId, MatchOptions, Region, StormClock, Timings};
+
+    fn time(seconds: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    /// Joins a raid on a new game.
+    fn join(summary: &mut Summary) {
+        join_instance(summary, "Prospect-1");
+    }
+
+    /// Joins a raid on the game instance.
+    fn join_instance(summary: &mut Summary, instance_id: &str) {
+        let game = Game::new(
+            instance_id.parse::<InstanceId>().unwrap(),
+            Region::Europe,
+            GameMap::BrightSands(Timings::for_map("Map01")),
+            StormClock::default(),
+            MatchOptions::default(),
+            None,
+            time(0),
+        );
+        summary.on_state_update(Some(game), std::sync::Arc::new(StateHolder::new()));
+    }
+
+    fn escape(seconds: i64, own: bool) -> Action {
+        Action::PlayerEscaped(events::PlayerEscaped::new(
+            time(seconds),
+            chrono::Duration::seconds(15),
+            "Player escaped".to_string(),
+            own,
+        ))
+    }
+
+    #[test]
+    fn escapes_of_teammates_are_not_counted() {
+        let mut summary = Summary::default();
+        join(&mut summary);
+        summary.on_action(&escape(60, false));
+        assert_eq!(summary.raid().unwrap().result, None);
+        summary.on_action(&escape(90, true));
+        assert_eq!(summary.raid().unwrap().result, Some(RaidResult::Escaped));
+    }
+
+    #[test]
+    fn player_counts_extend_the_raid() {
+        let mut summary = Summary::default();
+        join(&mut summary);
+        summary.on_action(&Action::TotalPlayerCountUpdate(
+            events::TotalPlayerCountUpdate::new(4, time(300)),
+        ));
+        summary.on_action(&Action::NearPlayerCountUpdate(
+            events::NearPlayerCountUpdate::new(1, time(600)),
+        ));
+        let raid = summary.raid().unwrap();
+        assert_eq!(raid.last_seen, time(600));
+        assert_eq!(raid.peak_players, 4);
+        assert_eq!(raid.encountered, 1);
+    }
+
+    #[test]
+    fn rejoined_raids_are_counted_once() {
+        let mut summary = Summary::default();
+        let lobby = |summary: &mut Summary| {
+            summary.on_state_update(None, std::sync::Arc::new(StateHolder::new()));
+        };
+
+        // An escape, then a rejoin of the same instance from the lobby
+        join_instance(&mut summary, "Prospect-1");
+        summary.on_action(&escape(60, true));
+        lobby(&mut summary);
+        join_instance(&mut summary, "Prospect-1");
+        lobby(&mut summary);
+        let totals = Totals {
+            raids: 1,
+            escapes: 1,
+            deaths: 0,
+        };
+        assert_eq!(summary.totals(), totals);
+
+        // A death, then a rejoin of the same instance from the lobby
+        join_instance(&mut summary, "Prospect-2");
+        summary.on_action(&Action::PlayerDead(events::PlayerDead::new(
+            time(120),
+            chrono::Duration::seconds(15),
+            None,
+            0,
+            None,
+            0.0,
+            true,
+        )));
+        lobby(&mut summary);
+        join_instance(&mut summary, "Prospect-2");
+        lobby(&mut summary);
+        let totals = Totals {
+            raids: 2,
+            escapes: 1,
+            deaths: 1,
+        };
+        assert_eq!(summary.totals(), totals);
+    }
+}
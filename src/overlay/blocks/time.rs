@@ -19,9 +19,48 @@
 // SOFTWARE.
 
 //! `Time` is one of the widgets (blocks) of the `Overlay` component.
-//! It creates a block with timers until morning, day, evening, night and session restart.
+//! It creates a block with timers until each phase of the map's day cycle and session restart.
 
-use crate::objects::{Game, GameMap};
+use super::super::theme::Theme;
+use crate::audio::{Alert, Audio};
+use crate::config::{CountdownAlert, TimeConfig, TimeMode};
+use crate::objects::{Confidence, Game, GameMap};
+use crate::state::StateHolder;
+
+use std::collections::{HashMap, HashSet};
+
+/// The duration of the flashing message of a countdown alert in seconds.
+const FLASH_SECONDS: f64 = 5.0;
+
+/// The name of the server end as a target of the alerts.
+const SERVER: &str = "server";
+
+/// The name of the next storm as a target of the alerts and a column of the bar.
+const STORM: &str = "storm";
+
+/// The `Flash` struct is the message of a countdown alert flashing on the screen.
+struct Flash {
+    /// The name of the target, e.g. the phase.
+    target: String,
+    /// The moment of the target in milliseconds.
+    target_at: i64,
+    /// The time of `egui` when the message disappears, None if it is not shown yet.
+    until: Option<f64>,
+}
+
+/// The `Countdown` struct is a column of the widget, the time until a phase or the server end.
+struct Countdown {
+    /// The name of the column.
+    name: String,
+    /// The beginning of the phase in the cycle in milliseconds.
+    start: i64,
+    /// The duration of the phase in milliseconds.
+    duration: i64,
+    /// The time until the beginning in milliseconds.
+    millis: i64,
+    /// The color of the column.
+    color: egui::Color32,
+}
 
 /// The `Time` struct represents a time widget, containing game start and end times, and associated map data.
 pub struct Time {
@@ -31,8 +70,22 @@ pub struct Time {
     game_end: i64,
     /// Optional game map information associated with the current game.
     map: Option<GameMap>,
+    /// Uncertainty of the storm clock in seconds, None if the clock is unknown.
+    uncertainty: Option<i64>,
+    /// Confidence level of the storm clock.
+    confidence: Confidence,
     /// Boolean representing whether the widget is visible.
     visible: bool,
+    /// The display mode, the hidden columns and the alerts.
+    config: TimeConfig,
+    /// The audio service which plays the countdown alerts.
+    audio: Audio,
+    /// The moments of the targets the alerts were fired for last, by the names of the alerts.
+    fired: HashMap<String, i64>,
+    /// The flashing message of the latest alert.
+    flash: Option<Flash>,
+    /// The names of the alerts whose targets were not found, which are reported once.
+    unresolved: HashSet<String>,
 }
 
 /// The `Default` implementation provides the initial state for the `Time` widget.
@@ -42,127 +95,161 @@ impl Default for Time {
             game_start: 0,
             game_end: 0,
             map: None,
+            uncertainty: None,
+            confidence: Confidence::Unknown,
             visible: false,
+            config: TimeConfig::default(),
+            audio: Audio::silent(),
+            fired: HashMap::new(),
+            flash: None,
+            unresolved: HashSet::new(),
         }
     }
 }
 
-impl Time {
+impl super::Block for Time {
     /// This method renders the `Time` widget to the UI.
     ///
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    pub fn show(&mut self, ui: &mut egui::Ui) {
-        // If no map data is present, stop the method execution.
-        if self.map.is_none() {
-            return;
-        }
+    fn show(&mut self, ui: &mut egui::Ui, theme: &Theme) {
+        // If no map data is present or the widget is hidden, stop the method execution.
+        let map = match self.map.as_ref() {
+            Some(map) if self.visible => map,
+            _ => return,
+        };
 
         // The current timestamp in milliseconds.
-        let now = chrono::Utc::now().timestamp_millis();
-
-        // Calculating the game time elapsed since the start.
-        let mut time = now - self.game_start;
-        // We clone the map and unwrap it because we have checked that it's not None.
-        let map = self.map.clone().unwrap();
+        let now = crate::utils::now().timestamp_millis();
         // Extract timing details from the map.
         let timings = map.timings();
-        // Get the remainder of time to determine the current phase (morning, day, evening, or night).
-        time %= timings.time_between_storms;
+        // Calculating the game time elapsed since the start, within the current cycle.
+        let time = (now - self.game_start).rem_euclid(timings.time_between_storms.max(1));
 
-        // Initializing the target time to 0.
-        // This will be updated with each time phase boundary.
+        // Calculate the time remaining until each phase of the cycle.
+        // The target time is updated with each phase boundary.
         let mut target = 0;
-
-        // Calculate the time remaining until each phase of the day.
-        let to_morning = Time::diff(time, target, timings.time_between_storms);
-        target += timings.morning;
-        let to_day = Time::diff(time, target, timings.time_between_storms);
-        target += timings.day;
-        let to_evening = Time::diff(time, target, timings.time_between_storms);
-        target += timings.evening;
-        let to_night = Time::diff(time, target, timings.time_between_storms);
+        let phases: Vec<Countdown> = timings
+            .phases
+            .iter()
+            .enumerate()
+            .map(|(i, phase)| {
+                let countdown = Countdown {
+                    name: phase.name.clone(),
+                    start: target,
+                    duration: phase.duration,
+                    millis: Time::diff(time, target, timings.time_between_storms),
+                    color: theme.phase_color(i, phase.color),
+                };
+                target += phase.duration;
+                countdown
+            })
+            .collect();
 
         // Calculate the time remaining until the server is due to shut down.
         let to_server_death = self.game_end - now;
+        let server_color = if to_server_death > chrono::Duration::minutes(45).num_milliseconds() {
+            theme.colors.server_death
+        } else {
+            theme.colors.warning
+        };
+        let server = Countdown {
+            name: "Server".to_string(),
+            start: 0,
+            duration: 0,
+            millis: to_server_death,
+            color: server_color,
+        };
 
-        // If the `Time` object is set to visible, we draw its UI elements.
-        if self.visible {
-            // Each time label is displayed with appropriate color coding.
-            // The if-else block determines the color of the server death time label.
-            // The show_label method call renders each label to the UI.
-            egui::Frame::none().show(ui, |ui| {
-                ui.vertical(|ui| {
+        // The alerts are fired by the tick, only their message is drawn here
+        let flash_color = std::iter::once(&server)
+            .chain(phases.iter())
+            .find(|countdown| {
+                self.flash
+                    .as_ref()
+                    .is_some_and(|f| f.target == countdown.name)
+            })
+            .map_or(theme.colors.timer, |countdown| countdown.color);
+        self.show_flash(ui, theme, now, flash_color);
+
+        egui::Frame::none().show(ui, |ui| {
+            ui.vertical(|ui| match self.config.mode {
+                TimeMode::Compact | TimeMode::Labelled => {
                     ui.horizontal(|ui| {
-                        super::super::show_label(
-                            ui,
-                            format!(
-                                "{}:{:02}",
-                                (to_morning / 60000),
-                                ((to_morning % 60000) / 1000)
-                            ),
-                            egui::Color32::from_rgb(0x00, 0xcc, 0xff),
-                            egui::FontFamily::Name("MonospaceX".into()),
-                            28.0,
-                        );
-                        super::super::show_label(
-                            ui,
-                            format!("/ {}:{:02}", (to_day / 60000), ((to_day % 60000) / 1000)),
-                            egui::Color32::from_rgb(0xff, 0xff, 0x00),
-                            egui::FontFamily::Name("MonospaceX".into()),
-                            28.0,
-                        );
-                        super::super::show_label(
-                            ui,
-                            format!(
-                                "/ {}:{:02}",
-                                (to_evening / 60000),
-                                ((to_evening % 60000) / 1000)
-                            ),
-                            egui::Color32::from_rgb(0xff, 0xef, 0xd5),
-                            egui::FontFamily::Name("MonospaceX".into()),
-                            28.0,
-                        );
-                        super::super::show_label(
-                            ui,
-                            format!(
-                                "/ {}:{:02}",
-                                (to_night / 60000),
-                                ((to_night % 60000) / 1000)
-                            ),
-                            egui::Color32::from_rgb(0xff, 0x00, 0x99),
-                            egui::FontFamily::Name("MonospaceX".into()),
-                            28.0,
-                        );
+                        let columns = phases
+                            .iter()
+                            .chain(std::iter::once(&server))
+                            .filter(|column| self.config.shows(&column.name));
+                        for (i, column) in columns.enumerate() {
+                            // All labels except the first one are separated by a slash
+                            let delimiter = if i > 0 { "/ " } else { "" };
+                            if self.config.mode == TimeMode::Labelled {
+                                super::super::show_label(
+                                    ui,
+                                    format!("{}{}", delimiter, column.name.to_uppercase()),
+                                    column.color,
+                                    egui::FontFamily::Name("MonospaceX".into()),
+                                    theme.font_sizes.detail,
+                                );
+                                super::super::show_label(
+                                    ui,
+                                    self.format_known(column.millis),
+                                    column.color,
+                                    egui::FontFamily::Name("MonospaceX".into()),
+                                    theme.font_sizes.time,
+                                );
+                            } else {
+                                super::super::show_label(
+                                    ui,
+                                    format!("{}{}", delimiter, self.format_known(column.millis)),
+                                    column.color,
+                                    egui::FontFamily::Name("MonospaceX".into()),
+                                    theme.font_sizes.time,
+                                );
+                            }
+                        }
+                        self.show_accuracy(ui, theme);
+                    });
+                }
+                TimeMode::Bar => self.show_bar(ui, theme, time, &phases, &server),
+            })
+        });
+    }
 
-                        let color = if to_server_death
-                            > chrono::Duration::milliseconds(2700000).num_milliseconds()
-                        {
-                            egui::Color32::from_rgb(0x99, 0x66, 0x66)
-                        } else {
-                            egui::Color32::RED
-                        };
-
-                        super::super::show_label(
-                            ui,
-                            format!(
-                                "/ {}:{:02}",
-                                (to_server_death / 60000),
-                                ((to_server_death % 60000) / 1000)
-                            ),
-                            color,
-                            egui::FontFamily::Name("MonospaceX".into()),
-                            28.0,
-                        );
-                    })
-                })
-            });
-        }
+    /// This method fires the alerts of the countdowns which have crossed their thresholds, once a
+    /// second whether the widget is drawn or not.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn tick(&mut self) {
+        let map = match self.map.as_ref() {
+            Some(map) if self.visible && self.confidence != Confidence::Unknown => map,
+            _ => return,
+        };
+
+        let now = crate::utils::now().timestamp_millis();
+        let timings = map.timings();
+        let time = (now - self.game_start).rem_euclid(timings.time_between_storms.max(1));
+
+        // The time until each phase of the cycle
+        let mut target = 0;
+        let phases: Vec<(String, i64)> = timings
+            .phases
+            .iter()
+            .map(|phase| {
+                let millis = Time::diff(time, target, timings.time_between_storms);
+                target += phase.duration;
+                (phase.name.clone(), millis)
+            })
+            .collect();
+        self.check_alerts(now, self.game_end - now, &phases);
     }
 
     /// This method updates the state of the `Time` widget based on the game state.
@@ -170,23 +257,272 @@ impl Time {
     /// # Arguments
     ///
     /// * `game` - An Option that can contain the current game state.
+    /// * `_state` - A reference to the `StateHolder` which holds the state information.
     ///
     /// # Returns
     ///
     /// * None
-    pub fn on_state_update(&mut self, game: Option<Game>) {
+    fn on_state_update(&mut self, game: Option<Game>, _state: std::sync::Arc<StateHolder>) {
         // If a game state is present, update widget's data and make it visible
         if let Some(game) = game {
             self.map = Some(game.map.clone());
-            self.game_start = game.created_at.timestamp_millis() - game.map.timings().morning;
+            // The creation time of the game is estimated by its storm clock
+            let created_at = game.clock.created_at().unwrap_or_default();
+            self.game_start = created_at.timestamp_millis() - game.map.timings().start_offset;
             self.game_end =
-                game.created_at.timestamp_millis() + chrono::Duration::hours(6).num_milliseconds();
+                created_at.timestamp_millis() + chrono::Duration::hours(6).num_milliseconds();
+            self.uncertainty = game.clock.uncertainty().map(|u| u.num_seconds());
+            self.confidence = game.clock.confidence();
             self.visible = true;
         } else {
             // Otherwise, hide the widget
             self.visible = false;
         }
     }
+}
+
+impl Time {
+    /// Creates a new `Time` widget.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The display mode and the hidden columns.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Time`.
+    pub fn new(config: &TimeConfig, audio: Audio) -> Self {
+        Self {
+            config: config.clone(),
+            audio,
+            ..Default::default()
+        }
+    }
+
+    /// This method fires the alerts whose targets are closer than their thresholds. Each alert
+    /// is fired once for each moment of its target, however rarely it is checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current timestamp in milliseconds.
+    /// * `server` - The time until the server end in milliseconds.
+    /// * `phases` - The names of the phases with the time until them in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn check_alerts(&mut self, now: i64, server: i64, phases: &[(String, i64)]) {
+        let alerts: Vec<CountdownAlert> = self
+            .config
+            .alerts
+            .iter()
+            .filter(|alert| alert.enabled)
+            .cloned()
+            .collect();
+        for alert in alerts.iter() {
+            let countdown = if alert.target.eq_ignore_ascii_case(SERVER) {
+                Some(("Server".to_string(), server))
+            } else {
+                Time::find_target(phases, |(name, _)| name, &alert.target).cloned()
+            };
+            let (name, millis) = match countdown {
+                Some(countdown) => countdown,
+                None => {
+                    if self.unresolved.insert(alert.name.clone()) {
+                        log::warn!(
+                            "The target {} of the alert {} is not a phase of this map",
+                            alert.target,
+                            alert.name
+                        );
+                    }
+                    continue;
+                }
+            };
+            let before = alert.before as i64 * 1000;
+            if millis <= 0 || millis > before {
+                continue;
+            }
+
+            // A corrected storm clock moves the target a bit, which is still the same target
+            let target_at = now + millis;
+            let fired = self.fired.get(&alert.name);
+            if fired.is_some_and(|fired| (target_at - fired).abs() < before) {
+                continue;
+            }
+            self.fired.insert(alert.name.clone(), target_at);
+
+            log::info!("Alert {}: {} in {}", alert.name, name, Time::format(millis));
+            self.audio
+                .play(Alert::Custom(alert.name.clone()), crate::utils::now());
+            if alert.flash {
+                self.flash = Some(Flash {
+                    target: name,
+                    target_at,
+                    until: None,
+                });
+            }
+        }
+    }
+
+    /// This method displays the flashing message of the latest alert.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    /// * `now` - The current timestamp in milliseconds.
+    /// * `color` - The color of the target of the alert.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn show_flash(&mut self, ui: &mut egui::Ui, theme: &Theme, now: i64, color: egui::Color32) {
+        let time = ui.input().time;
+        let flash = match self.flash.as_mut() {
+            Some(flash) => flash,
+            None => return,
+        };
+        // The message is shown for a while from its first frame
+        let until = *flash.until.get_or_insert(time + FLASH_SECONDS);
+        if time >= until {
+            self.flash = None;
+            return;
+        }
+
+        // The message pulses twice a second
+        let opacity = 0.6 + 0.4 * (time * std::f64::consts::TAU * 2.0).cos() as f32;
+        super::super::show_label(
+            ui,
+            format!(
+                "{} IN {}",
+                flash.target.to_uppercase(),
+                Time::format((flash.target_at - now).max(0))
+            ),
+            color.linear_multiply(opacity),
+            egui::FontFamily::Name("MonospaceX".into()),
+            theme.font_sizes.time,
+        );
+        ui.ctx().request_repaint();
+    }
+
+    /// This method displays the accuracy of the storm clock unless it is hidden.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn show_accuracy(&self, ui: &mut egui::Ui, theme: &Theme) {
+        if !self.config.shows("accuracy") {
+            return;
+        }
+        let accuracy = match self.uncertainty {
+            Some(seconds) => format!("±{}s", seconds),
+            None => "±?".to_string(),
+        };
+        super::super::show_label(
+            ui,
+            accuracy,
+            Time::confidence_color(self.confidence, theme),
+            egui::FontFamily::Name("MonospaceX".into()),
+            theme.font_sizes.detail,
+        );
+    }
+
+    /// This method displays the day cycle as a bar with the current phase and its progress,
+    /// followed by the time until the next storm and the server end.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    /// * `time` - The time elapsed in the current cycle in milliseconds.
+    /// * `phases` - The countdowns of the phases.
+    /// * `server` - The countdown of the server end.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn show_bar(
+        &self,
+        ui: &mut egui::Ui,
+        theme: &Theme,
+        time: i64,
+        phases: &[Countdown],
+        server: &Countdown,
+    ) {
+        let known = self.confidence != Confidence::Unknown;
+        let cycle = phases
+            .iter()
+            .map(|phase| phase.duration)
+            .sum::<i64>()
+            .max(1);
+        let current = phases.iter().rposition(|phase| phase.start <= time);
+
+        // The phases of the cycle, the current one is highlighted
+        let size = egui::vec2(theme.font_sizes.time * 12.0, theme.font_sizes.detail * 0.6);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let x = |millis: i64| rect.left() + rect.width() * millis as f32 / cycle as f32;
+        for (i, phase) in phases.iter().enumerate() {
+            let segment = egui::Rect::from_x_y_ranges(
+                x(phase.start)..=x(phase.start + phase.duration),
+                rect.y_range(),
+            );
+            let color = if known && current == Some(i) {
+                phase.color
+            } else {
+                phase.color.linear_multiply(0.4)
+            };
+            ui.painter().rect_filled(segment, 0.0, color);
+        }
+        if known {
+            let marker = x(time);
+            ui.painter().line_segment(
+                [
+                    egui::pos2(marker, rect.top() - 2.0),
+                    egui::pos2(marker, rect.bottom() + 2.0),
+                ],
+                egui::Stroke::new(2.0, egui::Color32::WHITE),
+            );
+        }
+
+        ui.horizontal(|ui| {
+            // The current phase with its progress
+            if let Some(phase) = current.filter(|_| known).map(|i| &phases[i]) {
+                let progress = (time - phase.start) * 100 / phase.duration.max(1);
+                super::super::show_label(
+                    ui,
+                    format!("{} {}%", phase.name.to_uppercase(), progress),
+                    phase.color,
+                    egui::FontFamily::Name("MonospaceX".into()),
+                    theme.font_sizes.detail,
+                );
+            }
+
+            let storm = Time::find_target(phases, |phase| &phase.name, STORM);
+            let columns = storm
+                .into_iter()
+                .chain(std::iter::once(server))
+                .filter(|column| self.config.shows(&column.name));
+            for column in columns {
+                super::super::show_label(
+                    ui,
+                    format!(
+                        "{} {}",
+                        column.name.to_uppercase(),
+                        self.format_known(column.millis)
+                    ),
+                    column.color,
+                    egui::FontFamily::Name("MonospaceX".into()),
+                    theme.font_sizes.detail,
+                );
+            }
+            self.show_accuracy(ui, theme);
+        });
+    }
 
     /// This method calculates the time difference, accounting for time cycle wraparound.
     ///
@@ -208,4 +544,102 @@ impl Time {
             cycle + diff
         }
     }
+
+    /// This method finds the phase an alert or a column targets by its name. The next storm is
+    /// the phase named so, or the last one of the cycle, as the built-in maps have no such phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `phases` - The phases of the cycle in their order.
+    /// * `name` - A function which provides the name of a phase.
+    /// * `target` - The name of the target, in any case.
+    ///
+    /// # Returns
+    ///
+    /// * The targeted phase, None if the map has no such phase.
+    fn find_target<'a, T>(
+        phases: &'a [T],
+        name: impl Fn(&T) -> &String,
+        target: &str,
+    ) -> Option<&'a T> {
+        phases
+            .iter()
+            .find(|phase| name(phase).eq_ignore_ascii_case(target))
+            .or_else(|| phases.last().filter(|_| target.eq_ignore_ascii_case(STORM)))
+    }
+
+    /// This method formats a duration as minutes and seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `millis` - The duration in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * A `String` in the `m:ss` format.
+    fn format(millis: i64) -> String {
+        format!("{}:{:02}", millis / 60000, (millis % 60000) / 1000)
+    }
+
+    /// This method formats a countdown, or a placeholder if the storm clock is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `millis` - The countdown in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * A `String` in the `m:ss` format.
+    fn format_known(&self, millis: i64) -> String {
+        if self.confidence == Confidence::Unknown {
+            "-:--".to_string()
+        } else {
+            Time::format(millis)
+        }
+    }
+
+    /// This method returns the color that represents the confidence level of the storm clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `confidence` - The confidence level.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * A `Color32` of the confidence level.
+    fn confidence_color(confidence: Confidence, theme: &Theme) -> egui::Color32 {
+        match confidence {
+            Confidence::High => theme.colors.confidence_high,
+            Confidence::Medium => theme.colors.confidence_medium,
+            Confidence::Low | Confidence::Unknown => theme.colors.confidence_low,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::Timings;
+
+    #[test]
+    fn default_alerts_resolve_on_built_in_maps() {
+        for map in ["MAP01", "MAP02", "AlienCaverns"] {
+            let timings = Timings::for_map(map);
+            for alert in TimeConfig::default().alerts.iter() {
+                let resolved = alert.target.eq_ignore_ascii_case(SERVER)
+                    || Time::find_target(&timings.phases, |phase| &phase.name, &alert.target)
+                        .is_some();
+                assert!(resolved, "{} on {} ({})", alert.name, map, timings.name);
+            }
+        }
+    }
+
+    #[test]
+    fn storm_is_the_last_phase_without_a_storm_phase() {
+        let timings = Timings::for_map("MAP01");
+        let storm = Time::find_target(&timings.phases, |phase| &phase.name, "Storm");
+        assert_eq!(storm.map(|phase| phase.name.as_str()), Some("Night"));
+        assert!(Time::find_target(&timings.phases, |phase| &phase.name, "dawn").is_none());
+    }
 }
@@ -21,43 +21,27 @@
 //! This module contains displayed environment events with timer, like
 //! when someone calls an evacuation ship or meteorites fall.
 
+use crate::objects::Evac;
+
 /// The `EvacShipCalled` struct represents an event when an evacuation ship is called in the game.
-/// It contains a timer, message and color.
-#[derive(Debug)]
+/// It follows the evac ship through the phases of its timeline.
+#[derive(Debug, Clone)]
 pub struct EvacShipCalled {
-    timer: super::EventTimer,
-    message: String,
-    color: egui::Color32,
+    evac: Evac,
 }
 
 impl EvacShipCalled {
-    /// Defining some constant colors to be used within the struct.
-    const PINK_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 175, 175);
-    const LIGHT_GRAY_COLOR: egui::Color32 = egui::Color32::from_rgb(192, 192, 192);
-    const GREEN_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 0);
-
     /// Constructs a new `EvacShipCalled` instance.
     ///
     /// # Arguments
     ///
-    /// * `time` - The start time of the event.
-    /// * `duration` - The duration of the event.
-    /// * `message` - The message to be displayed when the event occurs.
+    /// * `evac` - The called evac ship.
     ///
     /// # Returns
     ///
     /// * Self - A new instance of `EvacShipCalled`.
-    pub fn new(
-        time: chrono::DateTime<chrono::Utc>,
-        duration: chrono::Duration,
-        message: String,
-    ) -> Self {
-        let timer = super::EventTimer::new(time, duration);
-        Self {
-            timer,
-            message,
-            color: EvacShipCalled::GREEN_COLOR,
-        }
+    pub fn new(evac: Evac) -> Self {
+        Self { evac }
     }
 }
 
@@ -67,61 +51,104 @@ impl super::Event for EvacShipCalled {
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    fn show(&mut self, ui: &mut egui::Ui) {
-        let timer = self.timer.get_remaining_time();
-        if !timer.is_zero() {
-            // Update message and color based on remaining time
-            if timer.num_milliseconds() < chrono::Duration::seconds(76).num_milliseconds() {
-                if timer.num_milliseconds() < chrono::Duration::seconds(39).num_milliseconds() {
-                    self.message = "Evac ship [landed]".to_string();
-                }
-                if timer.num_milliseconds() < chrono::Duration::seconds(10).num_milliseconds() {
-                    self.message = "Evac ship [flying]".to_string();
-                    self.color = EvacShipCalled::LIGHT_GRAY_COLOR;
-                }
-            }
+    fn show(&mut self, ui: &mut egui::Ui, theme: &super::super::theme::Theme) {
+        let now = crate::utils::now();
+        let (phase, _) = match self.evac.phase_at(now) {
+            Some(phase) => phase,
+            None => return,
+        };
+        let remaining = self.evac.leaves_at() - now;
+        let color = match phase.inactive {
+            true => theme.colors.event_inactive,
+            false => theme.colors.event,
+        };
 
-            // Show the event in the UI
-            egui::Frame::none().show(ui, |ui| {
-                ui.horizontal(|ui| {
+        // Show the event in the UI
+        egui::Frame::none().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                super::super::show_label(
+                    ui,
+                    format!("[{:02}s]", remaining.num_seconds()),
+                    theme.colors.timer,
+                    egui::FontFamily::Name("MonospaceX".into()),
+                    theme.font_sizes.event,
+                );
+                super::super::show_label(
+                    ui,
+                    format!("Evac ship #{} [{}]", self.evac.sequence, phase.name),
+                    color,
+                    egui::FontFamily::Name("MonospaceX".into()),
+                    theme.font_sizes.event,
+                );
+                // The evac ship called by the player is marked
+                if self.evac.own {
                     super::super::show_label(
                         ui,
-                        format!("[{:02}s]", timer.num_seconds()),
-                        EvacShipCalled::PINK_COLOR,
+                        "OWN".to_string(),
+                        theme.colors.warning,
                         egui::FontFamily::Name("MonospaceX".into()),
-                        25.0,
+                        theme.font_sizes.event,
                     );
-                    super::super::show_label(
-                        ui,
-                        self.message.to_string(),
-                        self.color,
-                        egui::FontFamily::Name("MonospaceX".into()),
-                        25.0,
-                    );
-                });
+                }
             });
+        });
+    }
+
+    /// Returns the time until the evac ship leaves.
+    fn remaining(&self) -> chrono::Duration {
+        let remaining = self.evac.leaves_at() - crate::utils::now();
+        remaining.max(chrono::Duration::zero())
+    }
+
+    /// Returns the time of the event in the game log.
+    fn time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.evac.called_at
+    }
+
+    /// Returns the message of the event.
+    fn text(&self) -> String {
+        match self.evac.own {
+            true => format!("Evac ship #{} called (own)", self.evac.sequence),
+            false => format!("Evac ship #{} called", self.evac.sequence),
+        }
+    }
+
+    /// Moves the evac ship to the phase confirmed by the game log.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_action(&mut self, action: &super::Action) {
+        if let super::Action::EvacUpdate(update) = action {
+            if update.sequence != self.evac.sequence {
+                return;
+            }
+            if let Some(phase) = update.phase {
+                self.evac.advance(phase, update.time);
+            }
+            self.evac.own |= update.own;
         }
     }
 }
 
 /// The `MeteorsEvent` struct represents a meteor event in the game.
-/// It contains a timer, a message, and a color.
-#[derive(Debug)]
+/// It contains a timer and a message.
+#[derive(Debug, Clone)]
 pub struct MeteorsEvent {
     timer: super::EventTimer,
     message: String,
-    color: egui::Color32,
 }
 
 impl MeteorsEvent {
-    /// Defining some constant colors to be used within the struct.
-    const PINK_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 175, 175);
-    const GREEN_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 0);
-
     /// Constructs a new `MeteorsEvent` instance.
     ///
     /// # Arguments
@@ -139,11 +166,7 @@ impl MeteorsEvent {
         message: String,
     ) -> Self {
         let timer = super::EventTimer::new(time, duration);
-        Self {
-            timer,
-            message,
-            color: MeteorsEvent::GREEN_COLOR,
-        }
+        Self { timer, message }
     }
 }
 
@@ -153,11 +176,12 @@ impl super::Event for MeteorsEvent {
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    fn show(&mut self, ui: &mut egui::Ui) {
+    fn show(&mut self, ui: &mut egui::Ui, theme: &super::super::theme::Theme) {
         let timer = self.timer.get_remaining_time();
         if !timer.is_zero() {
             // Show the event in the UI
@@ -166,19 +190,39 @@ impl super::Event for MeteorsEvent {
                     super::super::show_label(
                         ui,
                         format!("[{:02}s]", timer.num_seconds()),
-                        MeteorsEvent::PINK_COLOR,
+                        theme.colors.timer,
                         egui::FontFamily::Name("MonospaceX".into()),
-                        25.0,
+                        theme.font_sizes.event,
                     );
                     super::super::show_label(
                         ui,
                         self.message.to_string(),
-                        self.color,
+                        theme.colors.event,
                         egui::FontFamily::Name("MonospaceX".into()),
-                        25.0,
+                        theme.font_sizes.event,
                     );
                 });
             });
         }
     }
+
+    /// Returns the time until the event expires.
+    fn remaining(&self) -> chrono::Duration {
+        self.timer.get_remaining_time()
+    }
+
+    /// Groups the meteor showers with the same message.
+    fn group_key(&self) -> Option<String> {
+        Some(format!("meteors:{}", self.message))
+    }
+
+    /// Returns the time of the event in the game log.
+    fn time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timer.start_time
+    }
+
+    /// Returns the message of the event.
+    fn text(&self) -> String {
+        self.message.clone()
+    }
 }
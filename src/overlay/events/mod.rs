@@ -25,8 +25,11 @@ mod players;
 mod state;
 
 pub use environment::{EvacShipCalled, MeteorsEvent};
-pub use players::{PlayerDead, PlayerEscaped};
-pub use state::{NearPlayerCountUpdate, TotalPlayerCountUpdate, UpdateState};
+pub use players::{CreatureKills, DeathRecap, PlayerDead, PlayerEscaped};
+pub use state::{
+    BrowseHistory, EvacUpdate, NearPlayerCountUpdate, ProximityAlert, SetEditMode,
+    TotalPlayerCountUpdate, UpdateState,
+};
 
 use std::fmt::Debug;
 use std::ops::Sub;
@@ -34,25 +37,85 @@ use std::ops::Sub;
 /// The Event trait defines an interface for all game events with timer that should be displayed in the event log.
 /// All these game events should implement this trait, allowing them to be displayed in the game's UI.
 pub trait Event: Debug + Send {
-    fn show(&mut self, ui: &mut egui::Ui);
+    /// Displays the event in the UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    fn show(&mut self, ui: &mut egui::Ui, theme: &super::theme::Theme);
+
+    /// Returns the time until the event expires, zero if it has expired.
+    fn remaining(&self) -> chrono::Duration;
+
+    /// Returns the key of repeated identical events, which are shown once with a counter.
+    /// Events are not grouped by default.
+    fn group_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the time of the event in the game log.
+    fn time(&self) -> chrono::DateTime<chrono::Utc>;
+
+    /// Returns the event as plain text for the history.
+    fn text(&self) -> String;
+
+    /// Returns whether the event belongs to the kill feed rather than to the activities.
+    fn is_kill(&self) -> bool {
+        false
+    }
+
+    /// Updates the event with a later action, e.g. with the next phase of an evac ship.
+    /// Events don't change by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    fn on_action(&mut self, _action: &Action) {}
 }
 
 /// The `Action` enum represents a generic game action.
-#[derive(Debug)]
+/// Actions are sent only a few times per second, so the size of the game state doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
 pub enum Action {
     PlayerDead(PlayerDead),
     PlayerEscaped(PlayerEscaped),
     EvacShipCalled(EvacShipCalled),
+    EvacUpdate(EvacUpdate),
     MeteorsEvent(MeteorsEvent),
     TotalPlayerCountUpdate(TotalPlayerCountUpdate),
     NearPlayerCountUpdate(NearPlayerCountUpdate),
     UpdateState(UpdateState),
+    SetEditMode(SetEditMode),
+    BrowseHistory(BrowseHistory),
+    DeathRecap(DeathRecap),
+    DismissRecap,
+    ProximityAlert(ProximityAlert),
+}
+
+impl Action {
+    /// Returns the event of the action which is displayed in the event log.
+    ///
+    /// # Returns
+    ///
+    /// * Some with a copy of the event, or None if the action is not a displayed event.
+    pub fn event(&self) -> Option<Box<dyn Event>> {
+        match self {
+            Action::EvacShipCalled(event) => Some(Box::new(event.clone())),
+            Action::MeteorsEvent(event) => Some(Box::new(event.clone())),
+            Action::PlayerEscaped(event) => Some(Box::new(event.clone())),
+            Action::PlayerDead(event) => Some(Box::new(event.clone())),
+            _ => None,
+        }
+    }
 }
 
 /// The `EventTimer` struct represents a timer for game events.
-/// It stores an end time for the event.
-#[derive(Debug)]
+/// It stores the start and end time of the event.
+#[derive(Debug, Clone)]
 struct EventTimer {
+    start_time: chrono::DateTime<chrono::Utc>,
     end_time: chrono::DateTime<chrono::Utc>,
 }
 
@@ -69,6 +132,7 @@ impl EventTimer {
     /// * None
     fn new(start_time: chrono::DateTime<chrono::Utc>, duration: chrono::Duration) -> Self {
         EventTimer {
+            start_time,
             end_time: start_time + duration,
         }
     }
@@ -76,10 +140,11 @@ impl EventTimer {
     /// Gets the remaining time for this event.
     /// If the event has already ended, it returns zero.
     fn get_remaining_time(&self) -> chrono::Duration {
-        if chrono::Utc::now().sub(self.end_time) > chrono::Duration::zero() {
+        let now = crate::utils::now();
+        if now.sub(self.end_time) > chrono::Duration::zero() {
             chrono::Duration::zero()
         } else {
-            self.end_time.sub(chrono::Utc::now())
+            self.end_time.sub(now)
         }
     }
 }
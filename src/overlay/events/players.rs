@@ -20,19 +20,18 @@
 
 //! This module contains player displayed events with timer, like when some player exits or dies.
 
+use crate::config::KillCauser;
+
 /// The `PlayerEscaped` struct represents a player escape event in the game.
-/// It contains a timer, a message, and a color.
-#[derive(Debug)]
+/// It contains a timer and a message.
+#[derive(Debug, Clone)]
 pub struct PlayerEscaped {
     timer: super::EventTimer,
     message: String,
-    color: egui::Color32,
+    own: bool,
 }
 
 impl PlayerEscaped {
-    /// Defining a constant color to be used within the struct.
-    const GREEN_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 0);
-
     /// Constructs a new `PlayerEscaped` instance.
     ///
     /// # Arguments
@@ -40,6 +39,7 @@ impl PlayerEscaped {
     /// * `time` - The start time of the event.
     /// * `duration` - The duration of the event.
     /// * `message` - The message to be displayed when the event occurs.
+    /// * `own` - Whether the player of the overlay escaped.
     ///
     /// # Returns
     ///
@@ -48,14 +48,20 @@ impl PlayerEscaped {
         time: chrono::DateTime<chrono::Utc>,
         duration: chrono::Duration,
         message: String,
+        own: bool,
     ) -> Self {
         let timer = super::EventTimer::new(time, duration);
         Self {
             timer,
             message,
-            color: PlayerEscaped::GREEN_COLOR,
+            own,
         }
     }
+
+    /// Returns whether the player of the overlay escaped.
+    pub fn own(&self) -> bool {
+        self.own
+    }
 }
 impl super::Event for PlayerEscaped {
     /// Displays the `PlayerEscaped` event in the UI.
@@ -63,39 +69,58 @@ impl super::Event for PlayerEscaped {
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    fn show(&mut self, ui: &mut egui::Ui) {
+    fn show(&mut self, ui: &mut egui::Ui, theme: &super::super::theme::Theme) {
         let timer = self.timer.get_remaining_time();
         if !timer.is_zero() {
             super::super::show_label(
                 ui,
                 self.message.to_string(),
-                self.color,
+                theme.colors.event,
                 egui::FontFamily::Name("MonospaceX".into()),
-                25.0,
+                theme.font_sizes.event,
             );
         }
     }
+
+    /// Returns the time until the event expires.
+    fn remaining(&self) -> chrono::Duration {
+        self.timer.get_remaining_time()
+    }
+
+    /// Groups the escapes with the same message.
+    fn group_key(&self) -> Option<String> {
+        Some(format!("escaped:{}", self.message))
+    }
+
+    /// Returns the time of the event in the game log.
+    fn time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timer.start_time
+    }
+
+    /// Returns the message of the event.
+    fn text(&self) -> String {
+        self.message.clone()
+    }
 }
 
 /// The `PlayerDead` struct represents a player death event in the game.
 /// It contains a timer, the actor causing the death, the weapon used, and the damage caused.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PlayerDead {
     timer: super::EventTimer,
     actor: Option<crate::objects::Actor>,
     actor_kills: usize,
     weapon: Option<crate::objects::Weapon>,
     damage: f32,
+    own: bool,
 }
 
 impl PlayerDead {
-    /// Defining a constant color to be used within the struct.
-    const GREEN_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 0);
-
     /// Constructs a new `PlayerDead` instance.
     ///
     /// # Arguments
@@ -106,6 +131,7 @@ impl PlayerDead {
     /// * `actor_kills` - The number of kills of actor.
     /// * `weapon` - The weapon used to cause the death.
     /// * `damage` - The amount of damage caused.
+    /// * `own` - Whether the player of the overlay died.
     ///
     /// # Returns
     ///
@@ -117,6 +143,7 @@ impl PlayerDead {
         actor_kills: usize,
         weapon: Option<crate::objects::Weapon>,
         damage: f32,
+        own: bool,
     ) -> Self {
         let timer = super::EventTimer::new(time, duration);
         Self {
@@ -125,8 +152,38 @@ impl PlayerDead {
             actor_kills,
             weapon,
             damage,
+            own,
+        }
+    }
+
+    /// Returns the category of the causer of the death.
+    ///
+    /// # Returns
+    ///
+    /// * `KillCauser` - The category, the environment if the causer is unknown.
+    pub fn causer(&self) -> KillCauser {
+        let actor = match self.actor.as_ref() {
+            Some(actor) => actor,
+            None => return KillCauser::Environment,
+        };
+        let weapon = self.weapon.as_ref().map(|weapon| weapon.name.as_str());
+        match (actor.name.as_str(), weapon) {
+            ("None", _) => KillCauser::Own,
+            ("Player", Some("Fall")) => KillCauser::Environment,
+            ("Player", _) => KillCauser::Player,
+            _ => KillCauser::Creature,
         }
     }
+
+    /// Returns the rarity of the weapon, or None if the death has no weapon.
+    pub fn weapon_rarity(&self) -> Option<&crate::objects::Rarity> {
+        self.weapon.as_ref().map(|weapon| &weapon.rarity)
+    }
+
+    /// Returns whether the player of the overlay died.
+    pub fn own(&self) -> bool {
+        self.own
+    }
 }
 impl super::Event for PlayerDead {
     /// Displays the `PlayerDead` event in the UI.
@@ -134,11 +191,12 @@ impl super::Event for PlayerDead {
     /// # Arguments
     ///
     /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
     ///
     /// # Returns
     ///
     /// * None
-    fn show(&mut self, ui: &mut egui::Ui) {
+    fn show(&mut self, ui: &mut egui::Ui, theme: &super::super::theme::Theme) {
         let timer = self.timer.get_remaining_time();
         let actor = self.actor.clone();
         let weapon = self.weapon.clone();
@@ -147,12 +205,9 @@ impl super::Event for PlayerDead {
         if !timer.is_zero() {
             egui::Frame::none().show(ui, |ui| {
                 let (message, color) = if let Some(actor) = actor {
-                    (
-                        format!("{} ", actor.name),
-                        egui::Color32::from(actor.rarity),
-                    )
+                    (format!("{} ", actor.name), theme.rarity(&actor.rarity))
                 } else {
-                    ("Something".to_string(), PlayerDead::GREEN_COLOR)
+                    ("Something".to_string(), theme.colors.event)
                 };
                 ui.horizontal(|ui| {
                     super::super::show_label(
@@ -160,50 +215,431 @@ impl super::Event for PlayerDead {
                         message,
                         color,
                         egui::FontFamily::Name("MonospaceX".into()),
-                        25.0,
+                        theme.font_sizes.event,
                     );
                     super::super::show_label(
                         ui,
                         "kills player (".to_string(),
-                        PlayerDead::GREEN_COLOR,
+                        theme.colors.event,
                         egui::FontFamily::Name("MonospaceX".into()),
-                        25.0,
+                        theme.font_sizes.event,
                     );
                     if let Some(weapon) = weapon {
                         super::super::show_label(
                             ui,
                             weapon.name.to_string(),
-                            egui::Color32::from(weapon.rarity),
+                            theme.rarity(&weapon.rarity),
                             egui::FontFamily::Name("MonospaceX".into()),
-                            25.0,
+                            theme.font_sizes.event,
                         );
                         super::super::show_label(
                             ui,
                             format!(": {:02.02} )", damage),
-                            PlayerDead::GREEN_COLOR,
+                            theme.colors.event,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            25.0,
+                            theme.font_sizes.event,
                         );
                     } else {
                         super::super::show_label(
                             ui,
                             format!("{:02.02} )", damage),
-                            PlayerDead::GREEN_COLOR,
+                            theme.colors.event,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            25.0,
+                            theme.font_sizes.event,
                         );
                     }
                     if self.actor_kills > 1 {
                         super::super::show_label(
                             ui,
                             format!("[x{:02.02}]", self.actor_kills),
-                            PlayerDead::GREEN_COLOR,
+                            theme.colors.event,
                             egui::FontFamily::Name("MonospaceX".into()),
-                            25.0,
+                            theme.font_sizes.event,
                         );
                     }
                 });
             });
         }
     }
+
+    /// Returns the time until the event expires.
+    fn remaining(&self) -> chrono::Duration {
+        self.timer.get_remaining_time()
+    }
+
+    /// Returns the time of the event in the game log.
+    fn time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timer.start_time
+    }
+
+    /// Returns the killer, the weapon and the damage.
+    fn text(&self) -> String {
+        let actor = self
+            .actor
+            .as_ref()
+            .map_or("Something".to_string(), |actor| actor.name.to_string());
+        let weapon = self
+            .weapon
+            .as_ref()
+            .map_or(String::new(), |weapon| format!("{}: ", weapon.name));
+        format!("{} kills player ({}{:.2})", actor, weapon, self.damage)
+    }
+
+    /// Deaths belong to the kill feed.
+    fn is_kill(&self) -> bool {
+        true
+    }
+}
+
+/// The `CreatureKills` struct represents the kills of AI creatures collapsed into one line.
+/// Each further kill by a creature is added to it until it expires.
+#[derive(Debug, Clone)]
+pub struct CreatureKills {
+    timer: super::EventTimer,
+    duration: chrono::Duration,
+    /// The names of the creatures with their rarities and kill counts, in the order of the first
+    /// kill.
+    creatures: Vec<(String, crate::objects::Rarity, usize)>,
+}
+
+impl CreatureKills {
+    /// The group key of the summary line.
+    pub const GROUP_KEY: &'static str = "kills:creatures";
+
+    /// Constructs a new `CreatureKills` instance from the first kill.
+    ///
+    /// # Arguments
+    ///
+    /// * `death` - The death caused by a creature.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `CreatureKills`.
+    pub fn new(death: &PlayerDead) -> Self {
+        // The line is shown after each kill as long as a single death
+        let duration = death.timer.end_time - death.timer.start_time;
+        let mut kills = Self {
+            timer: super::EventTimer::new(death.timer.start_time, duration),
+            duration,
+            creatures: Vec::new(),
+        };
+        kills.add(death);
+        kills
+    }
+
+    /// Adds a kill by a creature and shows the line longer.
+    ///
+    /// # Arguments
+    ///
+    /// * `death` - The death caused by a creature.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn add(&mut self, death: &PlayerDead) {
+        let (name, rarity) = match death.actor.as_ref() {
+            Some(actor) => (actor.name.clone(), actor.rarity.clone()),
+            None => return,
+        };
+        match self.creatures.iter_mut().find(|(n, _, _)| *n == name) {
+            Some((_, _, count)) => *count += 1,
+            None => self.creatures.push((name, rarity, 1)),
+        }
+        self.timer.end_time = self
+            .timer
+            .end_time
+            .max(death.timer.start_time + self.duration);
+    }
+}
+
+impl super::Event for CreatureKills {
+    /// Displays the `CreatureKills` line in the UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn show(&mut self, ui: &mut egui::Ui, theme: &super::super::theme::Theme) {
+        if self.timer.get_remaining_time().is_zero() {
+            return;
+        }
+        let font = egui::FontFamily::Name("MonospaceX".into());
+        let total: usize = self.creatures.iter().map(|(_, _, count)| count).sum();
+        ui.horizontal(|ui| {
+            super::super::show_label(
+                ui,
+                format!("AI kills {}:", total),
+                theme.colors.event,
+                font.clone(),
+                theme.font_sizes.event,
+            );
+            for (name, rarity, count) in self.creatures.iter() {
+                super::super::show_label(
+                    ui,
+                    format!("{} x{}", name, count),
+                    theme.rarity(rarity),
+                    font.clone(),
+                    theme.font_sizes.event,
+                );
+            }
+        });
+    }
+
+    /// Returns the time until the line expires.
+    fn remaining(&self) -> chrono::Duration {
+        self.timer.get_remaining_time()
+    }
+
+    /// Keeps a single summary line of the kills by creatures.
+    fn group_key(&self) -> Option<String> {
+        Some(Self::GROUP_KEY.to_string())
+    }
+
+    /// Returns the time of the first kill.
+    fn time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timer.start_time
+    }
+
+    /// Returns the creatures with their kill counts.
+    fn text(&self) -> String {
+        let creatures: Vec<String> = self
+            .creatures
+            .iter()
+            .map(|(name, _, count)| format!("{} x{}", name, count))
+            .collect();
+        format!("AI kills: {}", creatures.join(", "))
+    }
+
+    /// The summary belongs to the kill feed.
+    fn is_kill(&self) -> bool {
+        true
+    }
+
+    /// Adds the further kills by creatures while the line is shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn on_action(&mut self, action: &super::Action) {
+        if let super::Action::PlayerDead(death) = action {
+            if death.causer() == KillCauser::Creature && !self.remaining().is_zero() {
+                self.add(death);
+            }
+        }
+    }
+}
+
+/// The `DeathRecap` struct represents the recap of the death of the player, which is shown until
+/// it is dismissed or the player returns to the lobby.
+#[derive(Debug, Clone)]
+pub struct DeathRecap {
+    /// The death of the player.
+    death: PlayerDead,
+    /// The ID of the game instance the player died in.
+    instance_id: String,
+    /// The time the player was alive in the raid.
+    alive: chrono::Duration,
+    /// The number of nearby players before the death with the times of the changes.
+    near_players: Vec<(chrono::DateTime<chrono::Utc>, usize)>,
+    /// The actors with the most kills on the server with their names and kill counts.
+    leaderboard: Vec<(String, Option<crate::objects::Actor>, usize)>,
+}
+
+impl DeathRecap {
+    /// The period before the death covered by the history of nearby players.
+    const NEAR_PERIOD: i64 = 60;
+    /// The number of actors in the leaderboard.
+    const LEADERBOARD_SIZE: usize = 5;
+
+    /// Constructs a new `DeathRecap` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `death` - The death of the player.
+    /// * `game` - The game the player died in.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `DeathRecap`.
+    pub fn new(death: PlayerDead, game: &crate::objects::Game) -> Self {
+        let time = death.timer.start_time;
+        let near_players =
+            game.near_players_since(time - chrono::Duration::seconds(Self::NEAR_PERIOD));
+        // Resolve the names of the actors once, the recap is drawn every frame
+        let leaderboard = game
+            .leaderboard()
+            .into_iter()
+            .take(Self::LEADERBOARD_SIZE)
+            .map(|(id, kills)| {
+                let actor = crate::objects::Actor::get(id.class().to_string());
+                (id.class().to_string(), actor, kills)
+            })
+            .collect();
+
+        Self {
+            death,
+            instance_id: game.instance_id.to_string(),
+            alive: time - game.joined,
+            near_players,
+            leaderboard,
+        }
+    }
+
+    /// Returns the ID of the game instance the player died in.
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Displays the recap in the UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - A mutable reference to the `egui::Ui` instance.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn show(&self, ui: &mut egui::Ui, theme: &super::super::theme::Theme) {
+        let font = egui::FontFamily::Name("MonospaceX".into());
+        egui::Frame::none()
+            .fill(egui::Color32::from_black_alpha(160))
+            .stroke(egui::Stroke::new(1.0, theme.colors.server_death))
+            .inner_margin(egui::style::Margin::same(4.0))
+            .show(ui, |ui| {
+                ui.vertical(|ui| {
+                    // The death with the time alive in the raid
+                    let alive = self.alive.num_seconds().max(0);
+                    super::super::show_label(
+                        ui,
+                        format!(
+                            "YOU DIED after {:02}:{:02} in the raid",
+                            alive / 60,
+                            alive % 60
+                        ),
+                        theme.colors.warning,
+                        font.clone(),
+                        theme.font_sizes.server,
+                    );
+
+                    // The killer with the weapon and the damage
+                    ui.horizontal(|ui| {
+                        super::super::show_label(
+                            ui,
+                            "Killed by".to_string(),
+                            theme.colors.event,
+                            font.clone(),
+                            theme.font_sizes.event,
+                        );
+                        let (killer, color) = match self.death.actor.as_ref() {
+                            Some(actor) => (actor.name.clone(), theme.rarity(&actor.rarity)),
+                            None => ("Something".to_string(), theme.colors.event),
+                        };
+                        super::super::show_label(
+                            ui,
+                            killer,
+                            color,
+                            font.clone(),
+                            theme.font_sizes.event,
+                        );
+                        if let Some(weapon) = self.death.weapon.as_ref() {
+                            super::super::show_label(
+                                ui,
+                                "with".to_string(),
+                                theme.colors.event,
+                                font.clone(),
+                                theme.font_sizes.event,
+                            );
+                            super::super::show_label(
+                                ui,
+                                weapon.name.to_string(),
+                                theme.rarity(&weapon.rarity),
+                                font.clone(),
+                                theme.font_sizes.event,
+                            );
+                        }
+                        super::super::show_label(
+                            ui,
+                            format!("({:.2} damage)", self.death.damage),
+                            theme.colors.event,
+                            font.clone(),
+                            theme.font_sizes.event,
+                        );
+                    });
+                    if self.death.actor_kills > 0 {
+                        super::super::show_label(
+                            ui,
+                            format!(
+                                "Kills by this killer on the server: {}",
+                                self.death.actor_kills
+                            ),
+                            theme.colors.event,
+                            font.clone(),
+                            theme.font_sizes.detail,
+                        );
+                    }
+
+                    // The nearby players counted back from the death
+                    let near = if self.near_players.is_empty() {
+                        "none".to_string()
+                    } else {
+                        self.near_players
+                            .iter()
+                            .map(|(time, count)| {
+                                let before = (self.death.timer.start_time - *time).num_seconds();
+                                format!("{} at -{}s", count, before)
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    super::super::show_label(
+                        ui,
+                        format!("Near players in the last {}s: {}", Self::NEAR_PERIOD, near),
+                        theme.colors.event,
+                        font.clone(),
+                        theme.font_sizes.detail,
+                    );
+
+                    // The actors with the most kills on the server
+                    if !self.leaderboard.is_empty() {
+                        super::super::show_label(
+                            ui,
+                            "Server leaderboard:".to_string(),
+                            theme.colors.event,
+                            font.clone(),
+                            theme.font_sizes.detail,
+                        );
+                    }
+                    for (place, (class, actor, kills)) in self.leaderboard.iter().enumerate() {
+                        let (name, color) = match actor {
+                            Some(actor) => (actor.name.clone(), theme.rarity(&actor.rarity)),
+                            None => (class.clone(), theme.colors.event),
+                        };
+                        super::super::show_label(
+                            ui,
+                            format!("{}. {} x{}", place + 1, name, kills),
+                            color,
+                            font.clone(),
+                            theme.font_sizes.detail,
+                        );
+                    }
+
+                    super::super::show_label(
+                        ui,
+                        "send `dismiss` to close".to_string(),
+                        theme.colors.event_inactive,
+                        font.clone(),
+                        theme.font_sizes.detail,
+                    );
+                });
+            });
+    }
 }
@@ -25,9 +25,11 @@ use crate::objects::Game;
 
 /// The `TotalPlayerCountUpdate` structure represents the total player count update in the game.
 /// It contains the current number of players.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TotalPlayerCountUpdate {
     pub players: usize,
+    /// The time of the change in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
 }
 
 impl TotalPlayerCountUpdate {
@@ -36,20 +38,23 @@ impl TotalPlayerCountUpdate {
     /// # Arguments
     ///
     /// * `players` - The current number of players.
+    /// * `time` - The time of the change in the game log.
     ///
     /// # Returns
     ///
     /// * Self - A new instance of `TotalPlayerCountUpdate`.
-    pub fn new(players: usize) -> Self {
-        Self { players }
+    pub fn new(players: usize, time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self { players, time }
     }
 }
 
 /// The `NearPlayerCountUpdate` structure represents number of enemy players near with the player
 /// including players who are in a team with the current user.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NearPlayerCountUpdate {
     pub players: usize,
+    /// The time of the change in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
 }
 
 /// Constructs a new `NearPlayerCountUpdate` instance.
@@ -57,19 +62,83 @@ pub struct NearPlayerCountUpdate {
 /// # Arguments
 ///
 /// * `players` - The current number of enemy players near with the player.
+/// * `time` - The time of the change in the game log.
 ///
 /// # Returns
 ///
 /// * Self - A new instance of `NearPlayerCountUpdate`.
 impl NearPlayerCountUpdate {
-    pub fn new(players: usize) -> Self {
-        Self { players }
+    pub fn new(players: usize, time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self { players, time }
+    }
+}
+
+/// The `ProximityAlert` structure represents an alert about players coming near.
+#[derive(Debug, Clone)]
+pub struct ProximityAlert {
+    /// The time of the alert in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
+}
+
+impl ProximityAlert {
+    /// Constructs a new `ProximityAlert` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the alert in the game log.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `ProximityAlert`.
+    pub fn new(time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self { time }
+    }
+}
+
+/// The `EvacUpdate` structure represents evidence about an evac ship found later in the game log.
+#[derive(Debug, Clone)]
+pub struct EvacUpdate {
+    /// The number of the evac ship in the game.
+    pub sequence: usize,
+    /// The index of the phase which begins, if the evidence marks a phase.
+    pub phase: Option<usize>,
+    /// Whether the evidence shows that the evac ship was called by the player.
+    pub own: bool,
+    /// The time of the evidence in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
+}
+
+impl EvacUpdate {
+    /// Constructs a new `EvacUpdate` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The number of the evac ship in the game.
+    /// * `phase` - The index of the phase which begins, if the evidence marks a phase.
+    /// * `own` - Whether the evac ship was called by the player.
+    /// * `time` - The time of the evidence in the game log.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `EvacUpdate`.
+    pub fn new(
+        sequence: usize,
+        phase: Option<usize>,
+        own: bool,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            sequence,
+            phase,
+            own,
+            time,
+        }
     }
 }
 
 /// The `UpdateState` structure represents a game state update.
 /// It contains the updated state of the game.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UpdateState {
     pub game: Option<Game>,
 }
@@ -88,3 +157,51 @@ impl UpdateState {
         Self { game }
     }
 }
+
+/// The `SetEditMode` structure represents a request to turn the edit mode of the overlay on or off.
+#[derive(Debug, Clone)]
+pub struct SetEditMode {
+    /// The requested mode, or None to toggle the current one.
+    pub enabled: Option<bool>,
+}
+
+impl SetEditMode {
+    /// Constructs a new `SetEditMode` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - The requested mode, or None to toggle the current one.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `SetEditMode`.
+    pub fn new(enabled: Option<bool>) -> Self {
+        Self { enabled }
+    }
+}
+
+/// The `BrowseHistory` structure represents a request to show, hide or browse the history of
+/// the matches.
+#[derive(Debug, Clone)]
+pub struct BrowseHistory {
+    /// Whether the history is shown, or None to toggle it.
+    pub visible: Option<bool>,
+    /// The number of matches to go back (positive) or forward (negative) in the history.
+    pub step: isize,
+}
+
+impl BrowseHistory {
+    /// Constructs a new `BrowseHistory` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `visible` - Whether the history is shown, or None to toggle it.
+    /// * `step` - The number of matches to go back (positive) or forward (negative).
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `BrowseHistory`.
+    pub fn new(visible: Option<bool>, step: isize) -> Self {
+        Self { visible, step }
+    }
+}
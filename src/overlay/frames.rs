@@ -0,0 +1,198 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the pacing of the overlay frames. The overlay is redrawn only when
+//! something changes: an action arrives, a second passes for the timers, or an animation of
+//! `egui` runs. The time spent on the frames is reported once per minute.
+
+use std::time::{Duration, Instant};
+
+/// How often the time spent on the frames is reported.
+const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The `RedrawSchedule` struct decides when the overlay has to be redrawn.
+pub struct RedrawSchedule {
+    /// Whether a redraw has been requested by an event.
+    requested: bool,
+    /// The next tick of the timers, at the beginning of a second.
+    next_tick: Instant,
+    /// Whether a tick of the timers has passed since it was taken last.
+    ticked: bool,
+    /// The deadline of the running animation, if any.
+    animation: Option<Instant>,
+}
+
+impl RedrawSchedule {
+    /// Creates a new `RedrawSchedule` instance, which draws the first frame immediately.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `RedrawSchedule`.
+    pub fn new() -> Self {
+        Self {
+            requested: true,
+            next_tick: RedrawSchedule::next_second(Instant::now()),
+            ticked: false,
+            animation: None,
+        }
+    }
+
+    /// Requests a redraw, e.g. after an action or a window event.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Schedules the next frame of the animation after a frame has been drawn.
+    ///
+    /// # Arguments
+    ///
+    /// * `repaint_after` - The delay after which `egui` wants to be redrawn.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn animate(&mut self, repaint_after: Duration) {
+        // Delays longer than a second are covered by the tick of the timers
+        self.animation =
+            (repaint_after < Duration::from_secs(1)).then(|| Instant::now() + repaint_after);
+    }
+
+    /// Checks whether the overlay has to be redrawn now, and resets the reasons for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current moment.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if a redraw is due.
+    pub fn take_due(&mut self, now: Instant) -> bool {
+        let mut due = std::mem::take(&mut self.requested);
+        if now >= self.next_tick {
+            self.next_tick = RedrawSchedule::next_second(now);
+            self.ticked = true;
+            due = true;
+        }
+        if self.animation.is_some_and(|deadline| now >= deadline) {
+            self.animation = None;
+            due = true;
+        }
+        due
+    }
+
+    /// Checks whether a tick of the timers has passed since the last call, and resets it.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the timers have ticked.
+    pub fn take_tick(&mut self) -> bool {
+        std::mem::take(&mut self.ticked)
+    }
+
+    /// Returns the moment until which the event loop can wait.
+    ///
+    /// # Returns
+    ///
+    /// * The next tick or the deadline of the animation, whichever comes first.
+    pub fn deadline(&self) -> Instant {
+        self.animation
+            .map_or(self.next_tick, |deadline| deadline.min(self.next_tick))
+    }
+
+    /// Calculates the beginning of the next second of the system clock, when the countdowns
+    /// change.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current moment.
+    ///
+    /// # Returns
+    ///
+    /// * The moment of the next second.
+    fn next_second(now: Instant) -> Instant {
+        let millis = chrono::Utc::now().timestamp_subsec_millis().min(999);
+        now + Duration::from_millis(1000 - millis as u64)
+    }
+}
+
+/// The `FrameStats` struct measures the time spent on the frames.
+pub struct FrameStats {
+    /// The beginning of the current report interval.
+    started: Instant,
+    /// The number of frames drawn in the interval.
+    frames: u32,
+    /// The CPU time spent on building the frames with `egui`.
+    build: Duration,
+    /// The time spent in the renderer, including the presentation of the frames.
+    render: Duration,
+    /// The longest frame in the interval.
+    longest: Duration,
+}
+
+impl FrameStats {
+    /// Creates a new `FrameStats` instance.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `FrameStats`.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            frames: 0,
+            build: Duration::ZERO,
+            render: Duration::ZERO,
+            longest: Duration::ZERO,
+        }
+    }
+
+    /// Records a frame and reports the statistics once per minute.
+    ///
+    /// # Arguments
+    ///
+    /// * `build` - The time spent on building the frame.
+    /// * `render` - The time spent on rendering and presenting the frame.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn record(&mut self, build: Duration, render: Duration) {
+        self.frames += 1;
+        self.build += build;
+        self.render += render;
+        self.longest = self.longest.max(build + render);
+
+        let elapsed = self.started.elapsed();
+        if elapsed >= REPORT_INTERVAL {
+            log::info!(
+                "Overlay frames in the last {}s: {}, build {:.1} ms, render {:.1} ms, longest frame {:.1} ms",
+                elapsed.as_secs(),
+                self.frames,
+                self.build.as_secs_f64() * 1000.0,
+                self.render.as_secs_f64() * 1000.0,
+                self.longest.as_secs_f64() * 1000.0,
+            );
+            *self = FrameStats::new();
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module renders the overlay offscreen into a PNG image, without a window or a GPU.
+//! It is used for screenshots of the overlay and for checking layout changes without the game.
+
+use super::events::Action;
+use super::renderer::software::SoftwareRenderer;
+
+use std::path::Path;
+
+/// The maximum number of frames drawn before the image is taken. The windows of `egui` need
+/// a few frames to find their size and position.
+const MAX_FRAMES: usize = 10;
+
+impl super::Overlay {
+    /// Applies the actions at the virtual time and renders the overlay into a PNG image.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - The actions which are handled before the rendering, in their order.
+    /// * `now` - The virtual time at which the overlay is shown.
+    /// * `path` - The path of the image.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error message if the image cannot be written.
+    pub fn screenshot(
+        mut self,
+        actions: Vec<Action>,
+        now: chrono::DateTime<chrono::Utc>,
+        path: &Path,
+    ) -> Result<(), String> {
+        // The countdowns and timers are calculated from the virtual time
+        crate::utils::set_virtual_now(Some(now));
+        // The sample match is not saved into the history of the real matches
+        self.history = super::history::History::default();
+        for action in actions {
+            self.handle_action(action);
+        }
+        // The alerts due at the virtual time flash in the image
+        self.tick();
+
+        // One point of `egui` is one pixel of the image, so only the configured scale is applied
+        let scale = self.config.window.scale.unwrap_or(1.0);
+        self.theme.scale(scale);
+        self.layout.scale(scale);
+        let (width, height) = (
+            (self.width * scale).round() as u32,
+            (self.height * scale).round() as u32,
+        );
+
+        let pixels = self.render_offscreen(width, height);
+        crate::utils::set_virtual_now(None);
+
+        write_png(path, width, height, &pixels)
+    }
+
+    /// Draws the frames of the overlay with the software renderer until the layout settles.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the image.
+    /// * `height` - The height of the image.
+    ///
+    /// # Returns
+    ///
+    /// * The last frame in premultiplied ARGB, row by row.
+    fn render_offscreen(&mut self, width: u32, height: u32) -> Vec<u32> {
+        let ctx = egui::Context::default();
+        ctx.set_fonts(super::font_definitions());
+        let mut renderer = SoftwareRenderer::default();
+        let size = winit::dpi::PhysicalSize::new(width, height);
+
+        for frame in 0..MAX_FRAMES {
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    egui::vec2(width as f32, height as f32),
+                )),
+                pixels_per_point: Some(1.0),
+                // Each frame is a second later, so the animations of the widgets finish
+                time: Some(frame as f64),
+                ..Default::default()
+            };
+            let full_output = ctx.run(input, |ctx| self.show(ctx));
+            let paint_jobs = ctx.tessellate(full_output.shapes);
+            renderer.render(&paint_jobs, &full_output.textures_delta, 1.0, size);
+
+            // The layout has settled when `egui` doesn't ask for another frame
+            if !full_output.repaint_after.is_zero() {
+                break;
+            }
+        }
+
+        renderer.pixels().to_vec()
+    }
+}
+
+/// Writes the frame into a PNG image.
+///
+/// # Arguments
+///
+/// * `path` - The path of the image.
+/// * `width` - The width of the frame.
+/// * `height` - The height of the frame.
+/// * `pixels` - The frame in premultiplied ARGB, row by row.
+///
+/// # Returns
+///
+/// * `Result<(), String>` - An error message if the image cannot be written.
+fn write_png(path: &Path, width: u32, height: u32, pixels: &[u32]) -> Result<(), String> {
+    // PNG stores straight alpha, so the colors are divided by it
+    let data: Vec<u8> = pixels
+        .iter()
+        .flat_map(|pixel| {
+            let [a, r, g, b] = pixel.to_be_bytes();
+            let straight = |c: u8| match a {
+                0 => 0,
+                a => ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8,
+            };
+            [straight(r), straight(g), straight(b), a]
+        })
+        .collect();
+
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&data).map_err(|e| e.to_string())
+}
@@ -0,0 +1,594 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the history of the matches, which keeps all events posted to the event
+//! log per match. The history is saved next to the configuration file, so it survives restarts
+//! of the overlay, and can be browsed in a panel toggled by a control command.
+
+use super::events::{BrowseHistory, Event};
+use super::theme::Theme;
+use crate::objects::{Game, MeteorShower};
+
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+/// The maximum number of matches in the history, the oldest ones are removed first.
+const MAX_MATCHES: usize = 50;
+
+/// The maximum number of events of a match shown in the panel at once.
+const MAX_VISIBLE_EVENTS: f32 = 12.0;
+
+/// How often the changes of the history are written to the file, besides the change of a match.
+const SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Enum representing the kind of an archived event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A player was killed.
+    Kill,
+    /// Any other activity, e.g. an evac ship or meteors.
+    Activity,
+}
+
+/// The `EventRecord` struct is an archived event.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EventRecord {
+    /// The time of the event in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
+    /// The kind of the event.
+    pub kind: EventKind,
+    /// The event as plain text.
+    pub text: String,
+}
+
+/// The `MeteorRecord` struct is an archived meteor shower with its moment in the day cycle.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MeteorRecord {
+    /// The time of the meteor shower in the game log.
+    pub time: chrono::DateTime<chrono::Utc>,
+    /// The phase of the day cycle, if the storm clock was known.
+    #[serde(default)]
+    pub phase: Option<String>,
+    /// The seconds since the beginning of the phase, if the storm clock was known.
+    #[serde(default)]
+    pub into_phase: Option<i64>,
+}
+
+impl From<&MeteorShower> for MeteorRecord {
+    /// Archives a meteor shower of the game.
+    fn from(shower: &MeteorShower) -> Self {
+        Self {
+            time: shower.time,
+            phase: shower.phase.clone(),
+            into_phase: shower.into_phase.map(|duration| duration.num_seconds()),
+        }
+    }
+}
+
+/// The `MatchRecord` struct is an archived match with its events.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MatchRecord {
+    /// The ID of the game instance as written in the game log.
+    pub instance_id: String,
+    /// The generated name of the game.
+    pub name: String,
+    /// The short region code and instance ID of the game.
+    pub session_id: String,
+    /// The name of the map.
+    pub map: String,
+    /// The moment the match was joined.
+    pub joined: chrono::DateTime<chrono::Utc>,
+    /// The build of the game client, if it was found in the log header.
+    #[serde(default)]
+    pub build: Option<String>,
+    /// The events of the match in their order.
+    #[serde(default)]
+    pub events: Vec<EventRecord>,
+    /// The meteor showers on the server, including those before the match was joined.
+    #[serde(default)]
+    pub meteors: Vec<MeteorRecord>,
+}
+
+/// The `Archive` struct is the content of the history file.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Archive {
+    /// The matches from the oldest to the latest one.
+    matches: Vec<MatchRecord>,
+}
+
+/// The `History` struct keeps the archive of the matches and the state of its panel.
+#[derive(Default)]
+pub struct History {
+    /// The path of the history file, or None if the history is kept only in memory.
+    path: Option<PathBuf>,
+    /// The archived matches.
+    archive: Archive,
+    /// The instance ID of the recorded match, or None before the first match.
+    current: Option<String>,
+    /// Whether the archive has changed since it was saved.
+    dirty: bool,
+    /// The moment the archive was saved last.
+    saved: Option<std::time::Instant>,
+    /// Whether the panel is shown.
+    visible: bool,
+    /// The shown match, counted back from the latest one.
+    selected: usize,
+}
+
+impl History {
+    /// Loads the history from the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the history file.
+    ///
+    /// # Returns
+    ///
+    /// * Self - The loaded history, or an empty one if the file doesn't exist or is broken.
+    pub fn load(path: PathBuf) -> Self {
+        let archive = match std::fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                log::error!("Cannot parse history file {:?}: {}", path, e);
+                Archive::default()
+            }),
+            // A missing history file just means there were no matches yet
+            Err(_) => Archive::default(),
+        };
+
+        Self {
+            path: Some(path),
+            archive,
+            current: None,
+            dirty: false,
+            saved: None,
+            visible: false,
+            selected: 0,
+        }
+    }
+
+    /// Starts recording a match. A match which is already archived, e.g. when the game log is
+    /// read again after a restart or the server is rejoined, is continued instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The joined game.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn begin(&mut self, game: &Game) {
+        let instance_id = game.instance_id.to_string();
+        if self.current.as_ref() == Some(&instance_id) {
+            return;
+        }
+        self.current = Some(instance_id.clone());
+        if self.find(&instance_id).is_some() {
+            return;
+        }
+
+        self.archive.matches.push(MatchRecord {
+            instance_id,
+            name: game.name.clone(),
+            session_id: format!("{} {}", game.region.short(), game.instance_id.short()),
+            map: game.map.name().to_string(),
+            joined: game.joined,
+            build: game.build.as_ref().map(|build| build.to_string()),
+            events: Vec::new(),
+            meteors: Vec::new(),
+        });
+        if self.archive.matches.len() > MAX_MATCHES {
+            self.archive.matches.remove(0);
+        }
+        // The panel keeps showing the same match
+        if self.selected > 0 {
+            self.selected += 1;
+        }
+        // A new match is saved at once, together with the last events of the previous one
+        self.dirty = true;
+        self.flush();
+    }
+
+    /// Records the meteor showers on the server of the recorded match.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The updated game.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn record_meteors(&mut self, game: &Game) {
+        let record = match self.find(&game.instance_id.to_string()) {
+            Some(record) => record,
+            None => return,
+        };
+        // Only the new showers are appended, so a game which lost its showers, e.g. when the
+        // server was rejoined, doesn't shorten the archived list
+        let last = record.meteors.last().map(|meteors| meteors.time);
+        let meteors: Vec<MeteorRecord> = game
+            .meteors()
+            .iter()
+            .filter(|shower| last.is_none_or(|last| shower.time > last))
+            .map(MeteorRecord::from)
+            .collect();
+        // Most updates of the game don't change the meteor showers
+        if meteors.is_empty() {
+            return;
+        }
+        record.meteors.extend(meteors);
+        self.dirty = true;
+    }
+
+    /// Records an event of the recorded match.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event posted to the event log.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn record(&mut self, event: &dyn Event) {
+        let record = EventRecord {
+            time: event.time(),
+            kind: if event.is_kill() {
+                EventKind::Kill
+            } else {
+                EventKind::Activity
+            },
+            text: event.text(),
+        };
+
+        let current = match self.current.clone() {
+            Some(current) => current,
+            None => return,
+        };
+        let latest = match self.find(&current) {
+            Some(latest) => latest,
+            None => return,
+        };
+        // The game log is read from the beginning, so the events may be posted again
+        if latest.events.contains(&record) {
+            return;
+        }
+        latest.events.push(record);
+        self.dirty = true;
+    }
+
+    /// Saves the changes of the history once in a while, so the file is not written on every
+    /// event.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current moment.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn tick(&mut self, now: std::time::Instant) {
+        if self
+            .saved
+            .is_none_or(|saved| now.duration_since(saved) >= SAVE_INTERVAL)
+        {
+            self.flush();
+        }
+    }
+
+    /// Saves the changes of the history at once, e.g. when the overlay is closed.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn flush(&mut self) {
+        if std::mem::take(&mut self.dirty) {
+            self.save();
+            self.saved = Some(std::time::Instant::now());
+        }
+    }
+
+    /// Shows, hides or browses the history.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request from the control command.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn browse(&mut self, request: &BrowseHistory) {
+        self.visible = request.visible.unwrap_or(!self.visible);
+        let last = self.archive.matches.len().saturating_sub(1);
+        self.selected = self
+            .selected
+            .checked_add_signed(request.step)
+            .unwrap_or(0)
+            .min(last);
+    }
+
+    /// Renders the panel of the history if it is shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The `egui` context for rendering.
+    /// * `theme` - The colors and font sizes of the overlay.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn show(&self, ctx: &egui::Context, theme: &Theme) {
+        if !self.visible {
+            return;
+        }
+
+        let font = egui::FontFamily::Name("MonospaceX".into());
+        egui::Window::new("History")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .frame(egui::Frame::none().fill(egui::Color32::from_black_alpha(200)))
+            .show(ctx, |ui| {
+                let count = self.archive.matches.len();
+                let index = count.saturating_sub(self.selected + 1);
+                let record = match self.archive.matches.get(index) {
+                    Some(record) => record,
+                    None => {
+                        super::show_label(
+                            ui,
+                            "HISTORY: no matches yet".to_string(),
+                            theme.colors.server,
+                            font.clone(),
+                            theme.font_sizes.server,
+                        );
+                        return;
+                    }
+                };
+
+                // The match and its position in the history
+                ui.horizontal(|ui| {
+                    super::show_label(
+                        ui,
+                        format!("HISTORY {}/{}: {}", index + 1, count, record.name),
+                        theme.colors.server,
+                        font.clone(),
+                        theme.font_sizes.server,
+                    );
+                    super::show_label(
+                        ui,
+                        format!(
+                            "[{}] {} {}{}",
+                            record.session_id,
+                            record.map,
+                            record
+                                .joined
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M"),
+                            record
+                                .build
+                                .as_ref()
+                                .map(|build| format!(" {}", build))
+                                .unwrap_or_default()
+                        ),
+                        theme.colors.server,
+                        font.clone(),
+                        theme.font_sizes.detail,
+                    );
+                });
+
+                // The meteor showers with their phases, to compare them with the storms
+                if !record.meteors.is_empty() {
+                    let phases: Vec<String> = record
+                        .meteors
+                        .iter()
+                        .map(|meteors| match (&meteors.phase, meteors.into_phase) {
+                            (Some(phase), Some(seconds)) => {
+                                format!("{} +{}:{:02}", phase, seconds / 60, seconds % 60)
+                            }
+                            _ => "?".to_string(),
+                        })
+                        .collect();
+                    super::show_label(
+                        ui,
+                        format!("METEORS {}: {}", record.meteors.len(), phases.join(", ")),
+                        theme.colors.event,
+                        font.clone(),
+                        theme.font_sizes.detail,
+                    );
+                }
+
+                // The events of the match with their local time
+                let row_height = theme.font_sizes.event * 1.2;
+                egui::ScrollArea::vertical()
+                    .max_height(row_height * MAX_VISIBLE_EVENTS)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        if record.events.is_empty() {
+                            super::show_label(
+                                ui,
+                                "No events".to_string(),
+                                theme.colors.event_inactive,
+                                font.clone(),
+                                theme.font_sizes.event,
+                            );
+                        }
+                        for event in record.events.iter() {
+                            ui.horizontal(|ui| {
+                                super::show_label(
+                                    ui,
+                                    event
+                                        .time
+                                        .with_timezone(&chrono::Local)
+                                        .format("%H:%M:%S")
+                                        .to_string(),
+                                    theme.colors.timer,
+                                    font.clone(),
+                                    theme.font_sizes.event,
+                                );
+                                let color = match event.kind {
+                                    EventKind::Kill => theme.colors.warning,
+                                    EventKind::Activity => theme.colors.event,
+                                };
+                                super::show_label(
+                                    ui,
+                                    event.text.clone(),
+                                    color,
+                                    font.clone(),
+                                    theme.font_sizes.event,
+                                );
+                            });
+                        }
+                    });
+
+                super::show_label(
+                    ui,
+                    "history prev / next / off".to_string(),
+                    theme.colors.event_inactive,
+                    font.clone(),
+                    theme.font_sizes.detail,
+                );
+            });
+    }
+
+    /// Finds an archived match by the ID of its game instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance_id` - The ID of the game instance as written in the game log.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the match, or None if it is not in the history.
+    fn find(&mut self, instance_id: &str) -> Option<&mut MatchRecord> {
+        // The recorded match is usually the latest one
+        self.archive
+            .matches
+            .iter_mut()
+            .rev()
+            .find(|record| record.instance_id == instance_id)
+    }
+
+    /// Saves the history to the file.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn save(&self) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let result = toml::to_string(&self.archive)
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(path, text).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            log::error!("Cannot save history file {:?}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{GameMap, InstanceId, MatchOptions, Region, StormClock, Timings};
+
+    fn game(instance_id: &str, joined: i64) -> Game {
+        Game::new(
+            instance_id.parse::<InstanceId>().unwrap(),
+            Region::Europe,
+            GameMap::BrightSands(Timings::for_map("Map01")),
+            StormClock::default(),
+            MatchOptions::default(),
+            None,
+            chrono::DateTime::from_timestamp(1_700_000_000 + joined, 0).unwrap(),
+        )
+    }
+
+    fn instance_ids(history: &History) -> Vec<&str> {
+        history
+            .archive
+            .matches
+            .iter()
+            .map(|record| record.instance_id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn matches_are_joined_at_the_log_time() {
+        let mut history = History::default();
+        let first = game("Prospect-1", 60);
+        history.begin(&first);
+        assert_eq!(history.archive.matches[0].joined, first.joined);
+    }
+
+    #[test]
+    fn replayed_matches_are_not_added_again() {
+        let mut history = History::default();
+        history.begin(&game("Prospect-1", 0));
+        history.begin(&game("Prospect-2", 100));
+        // The game log is read again from the beginning after a restart
+        history.begin(&game("Prospect-1", 0));
+        history.begin(&game("Prospect-2", 100));
+        history.begin(&game("Prospect-3", 200));
+        assert_eq!(
+            instance_ids(&history),
+            vec!["Prospect-1", "Prospect-2", "Prospect-3"]
+        );
+    }
+
+    #[test]
+    fn meteors_are_only_appended() {
+        let mut history = History::default();
+        let mut first = game("Prospect-1", 0);
+        history.begin(&first);
+        first.record_meteors(first.joined + chrono::Duration::minutes(5));
+        first.record_meteors(first.joined + chrono::Duration::minutes(10));
+        history.record_meteors(&first);
+        history.record_meteors(&first);
+        assert_eq!(history.archive.matches[0].meteors.len(), 2);
+
+        // A new game on the same server doesn't know the earlier showers
+        let mut rejoined = game("Prospect-1", 900);
+        history.record_meteors(&rejoined);
+        assert_eq!(history.archive.matches[0].meteors.len(), 2);
+        rejoined.record_meteors(rejoined.joined + chrono::Duration::minutes(1));
+        history.record_meteors(&rejoined);
+        assert_eq!(history.archive.matches[0].meteors.len(), 3);
+    }
+
+    #[test]
+    fn changes_are_saved_in_batches() {
+        let mut history = History::default();
+        history.begin(&game("Prospect-1", 0));
+        assert!(!history.dirty);
+
+        let mut rejoined = game("Prospect-1", 100);
+        rejoined.record_meteors(rejoined.joined);
+        history.record_meteors(&rejoined);
+        assert!(history.dirty);
+        assert_eq!(history.archive.matches[0].meteors.len(), 1);
+        // The last save was just now
+        history.tick(std::time::Instant::now());
+        assert!(history.dirty);
+        history.flush();
+        assert!(!history.dirty);
+    }
+}
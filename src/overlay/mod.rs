@@ -21,8 +21,17 @@
 mod backend;
 mod blocks;
 pub mod events;
+mod frames;
+mod headless;
+mod history;
+mod platform;
+mod renderer;
+mod scene;
+mod theme;
 
-use blocks::{log, server, time};
+pub use scene::Scene;
+
+use blocks::Block;
 
 /// The main component responsible for overlay display, request handling, and calling display functions
 /// for other widgets (blocks).
@@ -30,9 +39,16 @@ pub struct Overlay {
     width: f32,
     height: f32,
     state: std::sync::Arc<crate::state::StateHolder>,
-    server_block: server::Server,
-    event_block: log::Log,
-    time_block: time::Time,
+    blocks: Vec<Box<dyn Block>>,
+    /// The widgets which are not in the layout but still fire their alerts.
+    background: Vec<Box<dyn Block>>,
+    history: history::History,
+    theme: theme::Theme,
+    layout: theme::Layout,
+    config: crate::config::Config,
+    config_path: std::path::PathBuf,
+    edit_mode: bool,
+    software: bool,
 }
 
 impl Overlay {
@@ -43,18 +59,53 @@ impl Overlay {
     /// * `width` - The width of the overlay window.
     /// * `height` - The height of the overlay window.
     /// * `state` - The shared state holder.
+    /// * `config` - The user configuration with the theme and layout.
+    /// * `config_path` - The path where the placement of the window is saved, the history of
+    ///   the matches is kept next to it.
+    /// * `edit_mode` - Whether the overlay starts in the edit mode.
+    /// * `software` - Whether the software renderer is used instead of the GPU.
+    /// * `audio` - The audio service which plays the alerts of the widgets.
     ///
     /// # Returns
     ///
     /// A new `Overlay` instance.
-    pub fn new(width: f32, height: f32, state: std::sync::Arc<crate::state::StateHolder>) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        width: f32,
+        height: f32,
+        state: std::sync::Arc<crate::state::StateHolder>,
+        config: &crate::config::Config,
+        config_path: std::path::PathBuf,
+        edit_mode: bool,
+        software: bool,
+        audio: crate::audio::Audio,
+    ) -> Self {
+        let layout = theme::Layout::from(&config.layout);
+        // Create the widgets listed in the layout
+        let registry = blocks::Registry::default();
+        let blocks = registry.build(&layout.blocks, config, &audio);
+        // The countdown alerts are fired even if the time block is not in the layout
+        let background = if layout.blocks.iter().any(|name| name == "time") {
+            Vec::new()
+        } else {
+            registry.build(&["time".to_string()], config, &audio)
+        };
+        // Load the events of the previous matches
+        let history = history::History::load(config_path.with_file_name("history.toml"));
+
         Self {
             width,
             height,
             state,
-            server_block: server::Server::default(),
-            event_block: log::Log::default(),
-            time_block: time::Time::default(),
+            blocks,
+            background,
+            history,
+            theme: theme::Theme::from(&config.theme),
+            layout,
+            config: config.clone(),
+            config_path,
+            edit_mode,
+            software,
         }
     }
 
@@ -68,22 +119,91 @@ impl Overlay {
     ///
     /// * None
     pub fn show(&mut self, ctx: &egui::Context) {
+        if self.edit_mode {
+            // Highlight the window area and explain how to leave the edit mode
+            egui::CentralPanel::default()
+                .frame(
+                    egui::Frame::none()
+                        .fill(egui::Color32::from_black_alpha(160))
+                        .stroke(egui::Stroke::new(2.0, self.theme.colors.server)),
+                )
+                .show(ctx, |ui| {
+                    show_label(
+                        ui,
+                        "EDIT MODE: drag to move, resize by the borders, send `edit` to save"
+                            .to_string(),
+                        self.theme.colors.server,
+                        egui::FontFamily::Name("MonospaceX".into()),
+                        self.theme.font_sizes.detail,
+                    );
+                });
+        }
+
         egui::Window::new("The Cycle: Overlay")
             .title_bar(false)
             .resizable(false)
-            .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(0.0, 0.0))
+            .anchor(self.layout.anchor, self.layout.offset)
             .frame(egui::Frame::none())
             .show(ctx, |ui| {
                 // Create new egui frame
                 egui::Frame::none().show(ui, |ui| {
-                    // Display server widget
-                    self.server_block.show(ui);
-                    // Display time widget
-                    self.time_block.show(ui);
-                    // Display events widget
-                    self.event_block.show(ui);
+                    // Display widgets in the order of the layout
+                    for (i, block) in self.blocks.iter_mut().enumerate() {
+                        if i > 0 {
+                            ui.add_space(self.layout.block_spacing);
+                        }
+                        block.show(ui, &self.theme);
+                    }
                 });
             });
+
+        // The history is shown over the blocks
+        self.history.show(ctx, &self.theme);
+    }
+
+    /// Passes an action to the widgets and records the events in the history.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action sent by the parsers.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn handle_action(&mut self, action: events::Action) {
+        match action {
+            events::Action::UpdateState(event) => {
+                if let Some(game) = event.game.as_ref() {
+                    self.history.begin(game);
+                    self.history.record_meteors(game);
+                }
+                // If the general state has been updated, we call the appropriate functions in each widget
+                for block in self.blocks.iter_mut().chain(self.background.iter_mut()) {
+                    block.on_state_update(event.game.clone(), self.state.clone());
+                }
+            }
+            events::Action::BrowseHistory(request) => self.history.browse(&request),
+            action => {
+                if let Some(event) = action.event() {
+                    self.history.record(event.as_ref());
+                }
+                // Other actions are handled by the widgets interested in them
+                for block in self.blocks.iter_mut().chain(self.background.iter_mut()) {
+                    block.on_action(&action);
+                }
+            }
+        }
+    }
+
+    /// Runs the work of the widgets which doesn't depend on drawing, once a second.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn tick(&mut self) {
+        for block in self.blocks.iter_mut().chain(self.background.iter_mut()) {
+            block.tick();
+        }
     }
 
     /// Runs the overlay and event loop.
@@ -97,132 +217,112 @@ impl Overlay {
     ///
     /// * None
     pub async fn run(mut self, event_loop: winit::event_loop::EventLoop<events::Action>) {
-        let mut backend = backend::Backend::new(self.width, self.height, event_loop);
+        let mut backend = backend::Backend::new(
+            self.width,
+            self.height,
+            &event_loop,
+            &self.config.window,
+            self.software,
+        );
+        if self.edit_mode {
+            backend.set_edit_mode(true);
+        }
+        // Scale the overlay to the resolution of the monitor
+        self.theme.scale(backend.scale);
+        self.layout.scale(backend.scale);
 
         let start_time = std::time::Instant::now();
-        backend.event_loop.run(move |event, _, control_flow| {
+        let mut schedule = frames::RedrawSchedule::new();
+        let mut stats = frames::FrameStats::new();
+        event_loop.run(move |event, _, control_flow| {
             // Handle `winit` with custom events
             backend.platform.handle_event(&event);
             match event {
                 winit::event::Event::RedrawRequested(..) => {
+                    let frame_start = std::time::Instant::now();
                     backend
                         .platform
                         .update_time(start_time.elapsed().as_secs_f64());
 
-                    let output_frame = match backend.surface.get_current_texture() {
-                        Ok(frame) => frame,
-                        Err(wgpu::SurfaceError::Outdated) => {
-                            // This error occurs when the app is minimized on Windows.
-                            // Silently return here to prevent spamming the console with:
-                            // "The underlying surface has changed, and therefore the swap chain must be updated"
-                            return;
-                        }
-                        Err(e) => {
-                            eprintln!("Dropped frame with error: {}", e);
-                            return;
-                        }
-                    };
-                    let output_view = output_frame
-                        .texture
-                        .create_view(&wgpu::TextureViewDescriptor::default());
-
                     // Begin to draw the UI frame
                     backend.platform.begin_frame();
 
                     // Draw the overlay
                     self.show(&backend.platform.context());
 
-                    // End the UI frame. We could now handle the output and draw the UI with the backend
+                    // End the UI frame and draw it with the renderer
                     let full_output = backend.platform.end_frame(Some(&backend.window));
-                    let paint_jobs = backend.platform.context().tessellate(full_output.shapes);
-
-                    let mut encoder =
-                        backend
-                            .device
-                            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                                label: Some("encoder"),
-                            });
-
-                    // Upload all resources for the GPU
-                    let screen_descriptor = egui_wgpu_backend::ScreenDescriptor {
-                        physical_width: backend.surface_config.width,
-                        physical_height: backend.surface_config.height,
-                        scale_factor: backend.window.scale_factor() as f32,
-                    };
-                    let t_delta: egui::TexturesDelta = full_output.textures_delta;
-                    backend
-                        .egui_rpass
-                        .add_textures(&backend.device, &backend.queue, &t_delta)
-                        .expect("add texture ok");
-                    backend.egui_rpass.update_buffers(
-                        &backend.device,
-                        &backend.queue,
-                        &paint_jobs,
-                        &screen_descriptor,
-                    );
-
-                    // Record all render passes
-                    backend
-                        .egui_rpass
-                        .execute(
-                            &mut encoder,
-                            &output_view,
-                            &paint_jobs,
-                            &screen_descriptor,
-                            Some(wgpu::Color::TRANSPARENT),
-                        )
-                        .unwrap();
-                    // Submit the commands
-                    backend.queue.submit(std::iter::once(encoder.finish()));
-
-                    // Redraw `egui`
-                    output_frame.present();
-
-                    backend
-                        .egui_rpass
-                        .remove_textures(t_delta)
-                        .expect("remove texture ok");
+                    schedule.animate(full_output.repaint_after);
+                    let build = frame_start.elapsed();
+                    backend.render(full_output);
+                    stats.record(build, frame_start.elapsed() - build);
                 }
                 winit::event::Event::MainEventsCleared => {
-                    backend.window.request_redraw();
-                }
-                winit::event::Event::WindowEvent { event, .. } => match event {
-                    winit::event::WindowEvent::CloseRequested => {
-                        *control_flow = winit::event_loop::ControlFlow::Exit;
+                    // Redraw only when something has changed, and sleep until the next change
+                    let now = std::time::Instant::now();
+                    if schedule.take_due(now) {
+                        backend.window.request_redraw();
+                    }
+                    if schedule.take_tick() {
+                        self.tick();
+                    }
+                    self.history.tick(now);
+                    if *control_flow != winit::event_loop::ControlFlow::Exit {
+                        *control_flow =
+                            winit::event_loop::ControlFlow::WaitUntil(schedule.deadline());
                     }
-                    _ => {}
-                },
-                winit::event::Event::UserEvent(events::Action::TotalPlayerCountUpdate(event)) => {
-                    // Update total number of players in the sever widget
-                    self.server_block.total_players = event.players;
-                }
-                winit::event::Event::UserEvent(events::Action::NearPlayerCountUpdate(event)) => {
-                    // Update near number of players in the sever widget
-                    self.server_block.near_players = event.players;
-                }
-                winit::event::Event::UserEvent(events::Action::EvacShipCalled(event)) => {
-                    // Post event in the event log widget with timer
-                    self.event_block.post(Box::new(event));
                 }
-                winit::event::Event::UserEvent(events::Action::MeteorsEvent(event)) => {
-                    // Post event in the event log widget with timer
-                    self.event_block.post(Box::new(event));
+                winit::event::Event::WindowEvent { event, .. } => {
+                    // Resizing, moving and other changes of the window need a new frame
+                    schedule.request();
+                    match event {
+                        winit::event::WindowEvent::CloseRequested => {
+                            *control_flow = winit::event_loop::ControlFlow::Exit;
+                        }
+                        winit::event::WindowEvent::Resized(size) => {
+                            backend.resize(size);
+                        }
+                        winit::event::WindowEvent::ScaleFactorChanged {
+                            new_inner_size, ..
+                        } => {
+                            backend.resize(*new_inner_size);
+                        }
+                        winit::event::WindowEvent::MouseInput {
+                            state: winit::event::ElementState::Pressed,
+                            button: winit::event::MouseButton::Left,
+                            ..
+                        } if self.edit_mode => {
+                            // The window can be dragged by any point in the edit mode
+                            if let Err(e) = backend.window.drag_window() {
+                                ::log::error!("Cannot drag the window: {}", e);
+                            }
+                        }
+                        _ => {}
+                    }
                 }
-                winit::event::Event::UserEvent(events::Action::PlayerEscaped(event)) => {
-                    // Post event in the event log widget with timer
-                    self.event_block.post(Box::new(event));
+                winit::event::Event::UserEvent(events::Action::SetEditMode(event)) => {
+                    schedule.request();
+                    let enabled = event.enabled.unwrap_or(!self.edit_mode);
+                    if enabled != self.edit_mode {
+                        self.edit_mode = enabled;
+                        backend.set_edit_mode(enabled);
+                        // Save the placement of the window when leaving the edit mode
+                        if !enabled {
+                            backend.save_placement(&mut self.config.window);
+                            let path = crate::config::Config::window_path(&self.config_path);
+                            if let Err(e) = self.config.window.save(&path) {
+                                ::log::error!("Cannot save the window placement: {}", e);
+                            }
+                        }
+                    }
                 }
-                winit::event::Event::UserEvent(events::Action::PlayerDead(event)) => {
-                    // Post event in the event log widget with timer
-                    self.event_block.post(Box::new(event));
+                winit::event::Event::UserEvent(action) => {
+                    schedule.request();
+                    self.handle_action(action);
                 }
-                winit::event::Event::UserEvent(events::Action::UpdateState(event)) => {
-                    // If the general state has been updated, we call the appropriate functions in each widget
-                    self.server_block
-                        .on_state_update(event.game.clone(), self.state.clone());
-                    self.time_block.on_state_update(event.game.clone());
-                    self.event_block
-                        .on_state_update(event.game, self.state.clone());
+                winit::event::Event::LoopDestroyed => {
+                    // The changes since the last save are not lost when the overlay is closed
+                    self.history.flush();
                 }
                 _ => (),
             }
@@ -230,6 +330,25 @@ impl Overlay {
     }
 }
 
+/// Returns the fonts of the overlay with the custom monospace font.
+///
+/// # Returns
+///
+/// * The font definitions for `egui`.
+fn font_definitions() -> egui::FontDefinitions {
+    let mut custom_fonts = egui::FontDefinitions::default();
+    let font_data = include_bytes!("fonts/Monospac821 WGL4 BT Bold.ttf");
+    custom_fonts.font_data.insert(
+        "custom_monospace".to_owned(),
+        egui::FontData::from_static(font_data),
+    );
+    custom_fonts.families.insert(
+        egui::FontFamily::Name("MonospaceX".into()),
+        vec!["custom_monospace".to_owned()],
+    );
+    custom_fonts
+}
+
 /// Displays a labeled message with specified color, font family, and font size.
 ///
 /// # Arguments
@@ -0,0 +1,118 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the platform-specific setup of the overlay window, which makes it
+//! transparent and lets the mouse events go through it, and presents the frames of
+//! the software renderer.
+
+#[cfg(windows)]
+mod windows;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod x11;
+
+/// The `WindowPlatform` trait defines the platform-specific operations on the overlay window.
+pub trait WindowPlatform {
+    /// Checks and completes the setup of the created window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    fn setup(&self, _window: &winit::window::Window) {}
+
+    /// Makes the window unresponsive to mouse events, or restores them.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    /// * `enabled` - Whether the mouse events go through the window.
+    fn set_click_through(&self, window: &winit::window::Window, enabled: bool);
+
+    /// Copies a frame of the software renderer into the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    /// * `pixels` - The frame in premultiplied ARGB, row by row.
+    /// * `width` - The width of the frame.
+    /// * `height` - The height of the frame.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error message if the frame cannot be presented.
+    fn present(
+        &self,
+        _window: &winit::window::Window,
+        _pixels: &[u32],
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), String> {
+        Err("software rendering is not supported on this platform".to_string())
+    }
+}
+
+/// Returns the implementation for the current platform.
+///
+/// # Returns
+///
+/// * The Windows implementation.
+#[cfg(windows)]
+pub fn current() -> Box<dyn WindowPlatform> {
+    Box::new(windows::Windows)
+}
+
+/// Returns the implementation for the current platform.
+///
+/// # Returns
+///
+/// * The X11 implementation, or a fallback if the X11 libraries cannot be loaded.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn current() -> Box<dyn WindowPlatform> {
+    match x11::X11::new() {
+        Ok(platform) => Box::new(platform),
+        Err(e) => {
+            log::error!("Cannot load X11 libraries: {}", e);
+            Box::new(Unsupported)
+        }
+    }
+}
+
+/// Returns the implementation for the current platform.
+///
+/// # Returns
+///
+/// * The fallback implementation.
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+pub fn current() -> Box<dyn WindowPlatform> {
+    Box::new(Unsupported)
+}
+
+/// The `Unsupported` struct is used on platforms where the window cannot be made click-through.
+#[cfg(not(windows))]
+struct Unsupported;
+
+#[cfg(not(windows))]
+impl WindowPlatform for Unsupported {
+    /// Reports that the window cannot be made click-through.
+    fn set_click_through(&self, _window: &winit::window::Window, enabled: bool) {
+        if enabled {
+            log::warn!("Click-through is not supported on this platform");
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the setup of the overlay window on Windows.
+
+use winit::platform::windows::WindowExtWindows;
+
+use std::ptr;
+
+/// The `Windows` struct changes the extended styles of the window with `winapi`.
+pub struct Windows;
+
+impl super::WindowPlatform for Windows {
+    /// Makes the window transparent and unresponsive to mouse events, or restores them.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    /// * `enabled` - Whether the mouse events go through the window.
+    fn set_click_through(&self, window: &winit::window::Window, enabled: bool) {
+        let hwnd = window.hwnd() as winapi::shared::windef::HWND;
+        let transparent = winapi::um::winuser::WS_EX_TRANSPARENT as i32;
+        unsafe {
+            let style = winapi::um::winuser::GetWindowLongA(hwnd, winapi::um::winuser::GWL_EXSTYLE)
+                | winapi::um::winuser::WS_EX_LAYERED as i32;
+            let style = if enabled {
+                style | transparent
+            } else {
+                style & !transparent
+            };
+            winapi::um::winuser::SetWindowLongA(hwnd, winapi::um::winuser::GWL_EXSTYLE, style);
+        }
+    }
+
+    /// Copies the frame into the layered window with per-pixel alpha.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    /// * `pixels` - The frame in premultiplied ARGB, row by row.
+    /// * `width` - The width of the frame.
+    /// * `height` - The height of the frame.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error message if the frame cannot be presented.
+    fn present(
+        &self,
+        window: &winit::window::Window,
+        pixels: &[u32],
+        width: u32,
+        height: u32,
+    ) -> Result<(), String> {
+        use winapi::um::{errhandlingapi, wingdi, winuser};
+
+        let hwnd = window.hwnd() as winapi::shared::windef::HWND;
+        unsafe {
+            let screen = winuser::GetDC(ptr::null_mut());
+            let memory = wingdi::CreateCompatibleDC(screen);
+
+            // A top-down 32-bit bitmap, whose BGRA layout matches the ARGB pixels in memory
+            let mut info: wingdi::BITMAPINFO = std::mem::zeroed();
+            info.bmiHeader.biSize = std::mem::size_of::<wingdi::BITMAPINFOHEADER>() as u32;
+            info.bmiHeader.biWidth = width as i32;
+            info.bmiHeader.biHeight = -(height as i32);
+            info.bmiHeader.biPlanes = 1;
+            info.bmiHeader.biBitCount = 32;
+            info.bmiHeader.biCompression = wingdi::BI_RGB;
+            let mut bits = ptr::null_mut();
+            let bitmap = wingdi::CreateDIBSection(
+                screen,
+                &info,
+                wingdi::DIB_RGB_COLORS,
+                &mut bits,
+                ptr::null_mut(),
+                0,
+            );
+            if bitmap.is_null() {
+                wingdi::DeleteDC(memory);
+                winuser::ReleaseDC(ptr::null_mut(), screen);
+                return Err("cannot create the bitmap".to_string());
+            }
+            let count = pixels.len().min((width * height) as usize);
+            ptr::copy_nonoverlapping(pixels.as_ptr(), bits as *mut u32, count);
+
+            // Replaces the content of the window with the bitmap
+            let previous = wingdi::SelectObject(memory, bitmap as _);
+            let mut size = winapi::shared::windef::SIZE {
+                cx: width as i32,
+                cy: height as i32,
+            };
+            let mut source = winapi::shared::windef::POINT { x: 0, y: 0 };
+            let mut blend = wingdi::BLENDFUNCTION {
+                BlendOp: wingdi::AC_SRC_OVER,
+                BlendFlags: 0,
+                SourceConstantAlpha: 255,
+                AlphaFormat: wingdi::AC_SRC_ALPHA,
+            };
+            let updated = winuser::UpdateLayeredWindow(
+                hwnd,
+                screen,
+                ptr::null_mut(),
+                &mut size,
+                memory,
+                &mut source,
+                0,
+                &mut blend,
+                winuser::ULW_ALPHA,
+            );
+            let error = errhandlingapi::GetLastError();
+
+            wingdi::SelectObject(memory, previous);
+            wingdi::DeleteObject(bitmap as _);
+            wingdi::DeleteDC(memory);
+            winuser::ReleaseDC(ptr::null_mut(), screen);
+
+            if updated == 0 {
+                return Err(format!("UpdateLayeredWindow failed with error {}", error));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the setup of the overlay window on Linux with X11. The mouse events go
+//! through the window thanks to an empty input shape, and the window is transparent thanks to
+//! an ARGB visual, which `winit` selects for transparent windows.
+
+use winit::platform::unix::WindowExtUnix;
+
+use std::ffi::CString;
+use std::ptr;
+
+/// The kind of the window shape which receives the input, `ShapeInput` of the X11 shape extension.
+const SHAPE_INPUT: i32 = 2;
+
+/// The `X11` struct changes the window with the dynamically loaded `Xlib` and `XFixes` libraries.
+pub struct X11 {
+    xlib: x11_dl::xlib::Xlib,
+    // The bindings of `XFixes` are named `Xlib` in `x11_dl`
+    xfixes: x11_dl::xfixes::Xlib,
+}
+
+impl X11 {
+    /// Loads the X11 libraries.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<X11, String>` - The implementation, or an error message if a library is missing.
+    pub fn new() -> Result<Self, String> {
+        Ok(Self {
+            xlib: x11_dl::xlib::Xlib::open().map_err(|e| e.to_string())?,
+            xfixes: x11_dl::xfixes::Xlib::open().map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// Returns the X11 display and window ID of the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the display and window ID, or None if the window doesn't use X11.
+    fn handles(
+        window: &winit::window::Window,
+    ) -> Option<(*mut x11_dl::xlib::Display, x11_dl::xlib::Window)> {
+        let display = window.xlib_display()? as *mut x11_dl::xlib::Display;
+        let xid = window.xlib_window()?;
+        Some((display, xid))
+    }
+}
+
+impl super::WindowPlatform for X11 {
+    /// Checks that the window can be transparent, which needs an ARGB visual and
+    /// a compositing manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    fn setup(&self, window: &winit::window::Window) {
+        let (display, screen) = match (X11::handles(window), window.xlib_screen_id()) {
+            (Some((display, _)), Some(screen)) => (display, screen),
+            _ => {
+                log::warn!("The window doesn't use X11, it may not be transparent");
+                return;
+            }
+        };
+
+        unsafe {
+            // `winit` selects the same 32-bit visual for transparent windows
+            let mut visual = std::mem::MaybeUninit::<x11_dl::xlib::XVisualInfo>::uninit();
+            let found = (self.xlib.XMatchVisualInfo)(
+                display,
+                screen,
+                32,
+                x11_dl::xlib::TrueColor,
+                visual.as_mut_ptr(),
+            );
+            if found == 0 {
+                log::warn!("No ARGB visual found, the overlay will not be transparent");
+            }
+
+            // Without a compositing manager the alpha channel is ignored
+            let name = CString::new(format!("_NET_WM_CM_S{}", screen)).unwrap();
+            let atom = (self.xlib.XInternAtom)(display, name.as_ptr(), x11_dl::xlib::False);
+            if (self.xlib.XGetSelectionOwner)(display, atom) == 0 {
+                log::warn!(
+                    "No compositing manager is running, the overlay will not be transparent"
+                );
+            }
+        }
+    }
+
+    /// Makes the window unresponsive to mouse events with an empty input shape, or restores
+    /// the default input shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    /// * `enabled` - Whether the mouse events go through the window.
+    fn set_click_through(&self, window: &winit::window::Window, enabled: bool) {
+        let (display, xid) = match X11::handles(window) {
+            Some(handles) => handles,
+            None => {
+                log::warn!("The window doesn't use X11, click-through is not supported");
+                return;
+            }
+        };
+
+        unsafe {
+            if enabled {
+                let region = (self.xfixes.XFixesCreateRegion)(display, ptr::null_mut(), 0);
+                (self.xfixes.XFixesSetWindowShapeRegion)(display, xid, SHAPE_INPUT, 0, 0, region);
+                (self.xfixes.XFixesDestroyRegion)(display, region);
+            } else {
+                // No region means the default shape, i.e. the whole window
+                (self.xfixes.XFixesSetWindowShapeRegion)(display, xid, SHAPE_INPUT, 0, 0, 0);
+            }
+            (self.xlib.XFlush)(display);
+        }
+    }
+
+    /// Copies the frame into the window. The ARGB visual of the window has the same layout
+    /// as the pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The overlay window.
+    /// * `pixels` - The frame in premultiplied ARGB, row by row.
+    /// * `width` - The width of the frame.
+    /// * `height` - The height of the frame.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error message if the frame cannot be presented.
+    fn present(
+        &self,
+        window: &winit::window::Window,
+        pixels: &[u32],
+        width: u32,
+        height: u32,
+    ) -> Result<(), String> {
+        let (display, xid) = X11::handles(window).ok_or("the window doesn't use X11")?;
+        if pixels.len() < (width * height) as usize {
+            return Err("the frame is smaller than the window".to_string());
+        }
+
+        unsafe {
+            let mut attributes = std::mem::MaybeUninit::<x11_dl::xlib::XWindowAttributes>::uninit();
+            if (self.xlib.XGetWindowAttributes)(display, xid, attributes.as_mut_ptr()) == 0 {
+                return Err("cannot get the window attributes".to_string());
+            }
+            let attributes = attributes.assume_init();
+
+            // The image borrows the pixels, so they must not be freed with it
+            let image = (self.xlib.XCreateImage)(
+                display,
+                attributes.visual,
+                attributes.depth as u32,
+                x11_dl::xlib::ZPixmap,
+                0,
+                pixels.as_ptr() as *mut std::os::raw::c_char,
+                width,
+                height,
+                32,
+                0,
+            );
+            if image.is_null() {
+                return Err("cannot create the image".to_string());
+            }
+
+            let gc = (self.xlib.XCreateGC)(display, xid, 0, ptr::null_mut());
+            (self.xlib.XPutImage)(display, xid, gc, image, 0, 0, 0, 0, width, height);
+            (self.xlib.XFreeGC)(display, gc);
+
+            (*image).data = ptr::null_mut();
+            (self.xlib.XDestroyImage)(image);
+            (self.xlib.XFlush)(display);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the GPU renderer of the `egui` output based on `wgpu`.
+
+/// The `GpuRenderer` struct holds the `wgpu` surface and device of the window.
+pub struct GpuRenderer {
+    surface: wgpu::Surface,
+    surface_config: wgpu::SurfaceConfiguration,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    egui_rpass: egui_wgpu_backend::RenderPass,
+}
+
+impl GpuRenderer {
+    /// Creates a new `GpuRenderer` instance for the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The window to draw into.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GpuRenderer, String>` - The renderer, or an error message if there is no
+    ///   suitable GPU adapter.
+    pub fn new(window: &winit::window::Window) -> Result<Self, String> {
+        // Instance creation for WGPU
+        let instance = wgpu::Instance::new(wgpu::Backends::PRIMARY);
+        let surface = unsafe { instance.create_surface(window) };
+
+        // Request for a compatible adapter
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: Some(&surface),
+            force_fallback_adapter: false,
+        }))
+        .ok_or("no suitable adapter")?;
+
+        // Request for a device and a command queue
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::default(),
+                limits: wgpu::Limits::default(),
+                label: None,
+            },
+            None,
+        ))
+        .map_err(|e| e.to_string())?;
+
+        // Surface configuration
+        let size = window.inner_size();
+        let surface_format = *surface
+            .get_supported_formats(&adapter)
+            .first()
+            .ok_or("the surface is incompatible with the adapter")?;
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        surface.configure(&device, &surface_config);
+
+        // RenderPass setup for `egui`
+        let egui_rpass = egui_wgpu_backend::RenderPass::new(&device, surface_format, 1);
+
+        Ok(Self {
+            surface,
+            surface_config,
+            device,
+            queue,
+            egui_rpass,
+        })
+    }
+
+    /// Draws the primitives into the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The window to draw into.
+    /// * `paint_jobs` - The tessellated `egui` output.
+    /// * `textures_delta` - The textures to update before drawing and to free after it.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn render(
+        &mut self,
+        window: &winit::window::Window,
+        paint_jobs: &[egui::ClippedPrimitive],
+        textures_delta: egui::TexturesDelta,
+    ) {
+        // Upload all resources for the GPU, even if the frame is dropped later
+        self.egui_rpass
+            .add_textures(&self.device, &self.queue, &textures_delta)
+            .expect("add texture ok");
+
+        let output_frame = match self.surface.get_current_texture() {
+            Ok(frame) => Some(frame),
+            Err(wgpu::SurfaceError::Outdated) => {
+                // This error occurs when the app is minimized on Windows.
+                // Silently skip the frame to prevent spamming the console with:
+                // "The underlying surface has changed, and therefore the swap chain must be updated"
+                None
+            }
+            Err(e) => {
+                eprintln!("Dropped frame with error: {}", e);
+                None
+            }
+        };
+
+        if let Some(output_frame) = output_frame {
+            let output_view = output_frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("encoder"),
+                });
+
+            let screen_descriptor = egui_wgpu_backend::ScreenDescriptor {
+                physical_width: self.surface_config.width,
+                physical_height: self.surface_config.height,
+                scale_factor: window.scale_factor() as f32,
+            };
+            self.egui_rpass.update_buffers(
+                &self.device,
+                &self.queue,
+                paint_jobs,
+                &screen_descriptor,
+            );
+
+            // Record all render passes
+            self.egui_rpass
+                .execute(
+                    &mut encoder,
+                    &output_view,
+                    paint_jobs,
+                    &screen_descriptor,
+                    Some(wgpu::Color::TRANSPARENT),
+                )
+                .unwrap();
+            // Submit the commands
+            self.queue.submit(std::iter::once(encoder.finish()));
+
+            // Redraw `egui`
+            output_frame.present();
+        }
+
+        self.egui_rpass
+            .remove_textures(textures_delta)
+            .expect("remove texture ok");
+    }
+
+    /// Reconfigures the surface after the window has been resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The new inner size of the window.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        // The surface cannot be configured with zero size, e.g. when the window is minimized
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+        self.surface.configure(&self.device, &self.surface_config);
+    }
+}
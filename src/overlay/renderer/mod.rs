@@ -0,0 +1,114 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the renderers of the `egui` output: the GPU one based on `wgpu`,
+//! and the CPU one, which is used if there is no suitable GPU adapter.
+
+mod gpu;
+pub mod software;
+
+/// The `Renderer` enum draws the tessellated `egui` output into the window.
+pub enum Renderer {
+    /// Rendering on the GPU with `wgpu`, boxed as its state is much larger than the software one.
+    Gpu(Box<gpu::GpuRenderer>),
+    /// Rendering on the CPU, presented by the platform-specific code.
+    Software(software::SoftwareRenderer),
+}
+
+impl Renderer {
+    /// Creates the renderer for the window, falling back to the software one if the GPU cannot
+    /// be used.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The window to draw into.
+    /// * `software` - Whether the software renderer is requested.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Renderer`.
+    pub fn new(window: &winit::window::Window, software: bool) -> Self {
+        if software {
+            log::info!("Software renderer is requested");
+            return Renderer::Software(software::SoftwareRenderer::default());
+        }
+
+        match gpu::GpuRenderer::new(window) {
+            Ok(renderer) => Renderer::Gpu(Box::new(renderer)),
+            Err(e) => {
+                log::error!(
+                    "Cannot use the GPU ({}), falling back to the software renderer",
+                    e
+                );
+                Renderer::Software(software::SoftwareRenderer::default())
+            }
+        }
+    }
+
+    /// Draws the primitives into the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The window to draw into.
+    /// * `window_platform` - The platform-specific code which presents the software output.
+    /// * `paint_jobs` - The tessellated `egui` output.
+    /// * `textures_delta` - The textures to update before drawing and to free after it.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn render(
+        &mut self,
+        window: &winit::window::Window,
+        window_platform: &dyn super::platform::WindowPlatform,
+        paint_jobs: &[egui::ClippedPrimitive],
+        textures_delta: egui::TexturesDelta,
+    ) {
+        match self {
+            Renderer::Gpu(renderer) => renderer.render(window, paint_jobs, textures_delta),
+            Renderer::Software(renderer) => {
+                let size = window.inner_size();
+                let pixels_per_point = window.scale_factor() as f32;
+                renderer.render(paint_jobs, &textures_delta, pixels_per_point, size);
+                if let Err(e) =
+                    window_platform.present(window, renderer.pixels(), size.width, size.height)
+                {
+                    renderer.report_present_error(e);
+                }
+            }
+        }
+    }
+
+    /// Adapts the renderer to the new size of the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The new inner size of the window.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        // The software renderer follows the size of the window on each frame
+        if let Renderer::Gpu(renderer) = self {
+            renderer.resize(size);
+        }
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the software renderer, which rasterizes the `egui` meshes on the CPU
+//! into premultiplied ARGB pixels. The pixels are presented by the platform-specific code.
+
+use egui::epaint::{ImageData, Mesh, Primitive, Vertex};
+use egui::{Color32, Pos2, TextureId};
+
+use std::collections::HashMap;
+
+/// The `Texture` struct is a copy of an `egui` texture in memory.
+struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color32>,
+}
+
+/// The `SoftwareRenderer` struct holds the textures and the rendered frame.
+#[derive(Default)]
+pub struct SoftwareRenderer {
+    /// The textures by their IDs.
+    textures: HashMap<TextureId, Texture>,
+    /// The rendered frame in premultiplied ARGB, row by row.
+    pixels: Vec<u32>,
+    /// The width of the rendered frame.
+    width: usize,
+    /// Whether an error of the presentation has already been reported.
+    present_failed: bool,
+}
+
+impl SoftwareRenderer {
+    /// Renders the primitives into the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `paint_jobs` - The tessellated `egui` output.
+    /// * `textures_delta` - The textures to update before drawing and to free after it.
+    /// * `pixels_per_point` - The number of pixels per `egui` point.
+    /// * `size` - The size of the frame.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn render(
+        &mut self,
+        paint_jobs: &[egui::ClippedPrimitive],
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) {
+        self.update_textures(textures_delta);
+
+        // Clear the frame to fully transparent pixels
+        let (width, height) = (size.width as usize, size.height as usize);
+        self.width = width;
+        self.pixels.clear();
+        self.pixels.resize(width * height, 0);
+
+        for job in paint_jobs {
+            // Paint callbacks need a GPU, so they are skipped
+            if let Primitive::Mesh(mesh) = &job.primitive {
+                let clip = job.clip_rect;
+                let clip = [
+                    ((clip.min.x * pixels_per_point).round().max(0.0) as usize).min(width),
+                    ((clip.min.y * pixels_per_point).round().max(0.0) as usize).min(height),
+                    ((clip.max.x * pixels_per_point).round().max(0.0) as usize).min(width),
+                    ((clip.max.y * pixels_per_point).round().max(0.0) as usize).min(height),
+                ];
+                self.draw_mesh(mesh, pixels_per_point, clip);
+            }
+        }
+
+        for id in &textures_delta.free {
+            self.textures.remove(id);
+        }
+    }
+
+    /// Returns the rendered frame.
+    ///
+    /// # Returns
+    ///
+    /// * The pixels in premultiplied ARGB, row by row.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Reports an error of the presentation, only the first one to avoid flooding the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error message.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn report_present_error(&mut self, error: String) {
+        if !self.present_failed {
+            log::error!("Cannot present the frame: {}", error);
+            self.present_failed = true;
+        }
+    }
+
+    /// Applies the new and changed textures.
+    ///
+    /// # Arguments
+    ///
+    /// * `textures_delta` - The textures to update.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn update_textures(&mut self, textures_delta: &egui::TexturesDelta) {
+        for (id, delta) in &textures_delta.set {
+            let (width, height, pixels): (usize, usize, Vec<Color32>) = match &delta.image {
+                ImageData::Color(image) => (image.size[0], image.size[1], image.pixels.clone()),
+                ImageData::Font(image) => (
+                    image.size[0],
+                    image.size[1],
+                    image.srgba_pixels(1.0).collect(),
+                ),
+            };
+
+            match delta.pos {
+                // Update a patch of the existing texture
+                Some([x, y]) => {
+                    if let Some(texture) = self.textures.get_mut(id) {
+                        for row in 0..height {
+                            let start = (y + row) * texture.width + x;
+                            texture.pixels[start..start + width]
+                                .copy_from_slice(&pixels[row * width..(row + 1) * width]);
+                        }
+                    }
+                }
+                None => {
+                    self.textures.insert(
+                        *id,
+                        Texture {
+                            width,
+                            height,
+                            pixels,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Rasterizes the triangles of the mesh.
+    ///
+    /// # Arguments
+    ///
+    /// * `mesh` - The mesh to draw.
+    /// * `pixels_per_point` - The number of pixels per `egui` point.
+    /// * `clip` - The clip rectangle in pixels, `[min_x, min_y, max_x, max_y]`.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn draw_mesh(&mut self, mesh: &Mesh, pixels_per_point: f32, clip: [usize; 4]) {
+        let texture = match self.textures.get(&mesh.texture_id) {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertex = |i: usize| {
+                let v: &Vertex = &mesh.vertices[triangle[i] as usize];
+                (
+                    Pos2::new(v.pos.x * pixels_per_point, v.pos.y * pixels_per_point),
+                    v,
+                )
+            };
+            let (a, va) = vertex(0);
+            let (mut b, mut vb) = vertex(1);
+            let (mut c, mut vc) = vertex(2);
+
+            // All triangles are drawn with the same orientation
+            let area = edge(a, b, c);
+            if area == 0.0 {
+                continue;
+            }
+            if area < 0.0 {
+                std::mem::swap(&mut b, &mut c);
+                std::mem::swap(&mut vb, &mut vc);
+            }
+            let area = area.abs();
+
+            // The bounding box of the triangle inside the clip rectangle
+            let min_x = (a.x.min(b.x).min(c.x).floor().max(0.0) as usize).max(clip[0]);
+            let min_y = (a.y.min(b.y).min(c.y).floor().max(0.0) as usize).max(clip[1]);
+            let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(clip[2]);
+            let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(clip[3]);
+
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    // Sample at the center of the pixel
+                    let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let (w0, w1, w2) = (edge(b, c, p), edge(c, a, p), edge(a, b, p));
+                    if !inside(w0, b, c) || !inside(w1, c, a) || !inside(w2, a, b) {
+                        continue;
+                    }
+                    let (w0, w1, w2) = (w0 / area, w1 / area, w2 / area);
+
+                    // Interpolate the texture coordinates and the color
+                    let u = va.uv.x * w0 + vb.uv.x * w1 + vc.uv.x * w2;
+                    let v = va.uv.y * w0 + vb.uv.y * w1 + vc.uv.y * w2;
+                    let texel = sample(texture, u, v);
+                    let channel = |f: fn(&Color32) -> u8| {
+                        let color = f(&va.color) as f32 * w0
+                            + f(&vb.color) as f32 * w1
+                            + f(&vc.color) as f32 * w2;
+                        color * f(&texel) as f32 / 255.0
+                    };
+                    let source = [
+                        channel(Color32::a),
+                        channel(Color32::r),
+                        channel(Color32::g),
+                        channel(Color32::b),
+                    ];
+
+                    let pixel = &mut self.pixels[y * self.width + x];
+                    *pixel = blend(*pixel, source);
+                }
+            }
+        }
+    }
+}
+
+/// Calculates the edge function, which is twice the signed area of the triangle `a`, `b`, `p`.
+///
+/// # Arguments
+///
+/// * `a` - The start of the edge.
+/// * `b` - The end of the edge.
+/// * `p` - The tested point.
+///
+/// # Returns
+///
+/// * The value, which is positive on one side of the edge and negative on the other.
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Checks whether a point is inside the edge. Points exactly on an edge belong to only one of
+/// the two triangles sharing it, so they are not blended twice.
+///
+/// # Arguments
+///
+/// * `w` - The edge function of the point.
+/// * `a` - The start of the edge.
+/// * `b` - The end of the edge.
+///
+/// # Returns
+///
+/// * `true` if the point is inside.
+fn inside(w: f32, a: Pos2, b: Pos2) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    w > 0.0 || (w == 0.0 && (dy < 0.0 || (dy == 0.0 && dx > 0.0)))
+}
+
+/// Samples the nearest texel of the texture.
+///
+/// # Arguments
+///
+/// * `texture` - The texture.
+/// * `u` - The horizontal texture coordinate from 0 to 1.
+/// * `v` - The vertical texture coordinate from 0 to 1.
+///
+/// # Returns
+///
+/// * The premultiplied color of the texel.
+fn sample(texture: &Texture, u: f32, v: f32) -> Color32 {
+    let x = ((u * texture.width as f32) as usize).min(texture.width.saturating_sub(1));
+    let y = ((v * texture.height as f32) as usize).min(texture.height.saturating_sub(1));
+    texture
+        .pixels
+        .get(y * texture.width + x)
+        .copied()
+        .unwrap_or(Color32::TRANSPARENT)
+}
+
+/// Blends the premultiplied source color over the pixel.
+///
+/// # Arguments
+///
+/// * `pixel` - The premultiplied ARGB pixel.
+/// * `source` - The premultiplied source color, `[a, r, g, b]` from 0 to 255.
+///
+/// # Returns
+///
+/// * The blended pixel.
+fn blend(pixel: u32, source: [f32; 4]) -> u32 {
+    let remaining = 1.0 - source[0] / 255.0;
+    source.iter().enumerate().fold(0, |result, (i, channel)| {
+        let shift = 24 - 8 * i as u32;
+        let destination = ((pixel >> shift) & 0xff) as f32;
+        let value = (channel + destination * remaining)
+            .round()
+            .clamp(0.0, 255.0) as u32;
+        result | (value << shift)
+    })
+}
@@ -0,0 +1,308 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the scenes of the screenshots: a game state and the actions the parsers
+//! would send, read from a TOML file. The times in a scene are given in seconds before the moment
+//! of the screenshot, so the same scene can be shown at any time.
+
+use super::events::{self, Action};
+use crate::config::EvacConfig;
+use crate::objects::{
+    Actor, Evac, EvacTimeline, Game, InstanceId, MatchOptions, Region, StormClock, Weapon,
+};
+use crate::state::StateHolder;
+
+use serde::Deserialize;
+
+use std::path::Path;
+use std::sync::Arc;
+
+/// The scene of the sample match, which is shown when no scene is given.
+const SAMPLE: &str = include_str!("sample.toml");
+
+/// The `Scene` struct is the content of a scene file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    /// The moment of the screenshot, the current time if omitted.
+    pub at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The joined game, None if the scene starts in the lobby.
+    game: Option<SceneGame>,
+    /// The actions in their order.
+    #[serde(default)]
+    actions: Vec<SceneAction>,
+}
+
+/// The `SceneGame` struct describes the joined game of a scene.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneGame {
+    /// The ID of the game instance as written in the game log.
+    instance_id: String,
+    /// The region of the server as written in the game log, e.g. `eu-central-1`.
+    region: String,
+    /// The map as written in the game log, e.g. `MAP01`.
+    map: String,
+    /// The seconds since the game was joined.
+    #[serde(default)]
+    joined: i64,
+    /// The server uptime in seconds reported by the handshake when the game was joined, which
+    /// sets the storm clock. The clock is unknown if omitted.
+    uptime: Option<i64>,
+    /// The size of the party.
+    #[serde(default)]
+    squad_size: Option<usize>,
+    /// The seconds since each meteor shower on the server, also before the game was joined.
+    #[serde(default)]
+    meteors: Vec<i64>,
+}
+
+/// The `SceneAction` enum is an action of a scene, with the seconds since it was sent.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum SceneAction {
+    /// The total number of players changed.
+    TotalPlayers { ago: i64, players: usize },
+    /// The number of nearby players changed.
+    NearPlayers { ago: i64, players: usize },
+    /// An evac ship was called, the evac ships are numbered in their order.
+    EvacCalled {
+        ago: i64,
+        #[serde(default)]
+        own: bool,
+    },
+    /// A meteor shower began.
+    Meteors { ago: i64 },
+    /// A player escaped.
+    Escaped {
+        ago: i64,
+        #[serde(default = "own_result")]
+        own: bool,
+    },
+    /// A player died, the death of the player opens the recap. The causer and the weapon are
+    /// given as written in the game log.
+    Dead {
+        ago: i64,
+        causer: String,
+        weapon: Option<String>,
+        damage: f32,
+        #[serde(default = "own_result")]
+        own: bool,
+    },
+    /// The player returned to the lobby.
+    Leave,
+}
+
+/// Provides the escapes and deaths of the player unless told otherwise.
+fn own_result() -> bool {
+    true
+}
+
+impl Scene {
+    /// Loads a scene from the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the scene file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Scene, String>` - The scene, or an error message if it cannot be read.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&text)
+    }
+
+    /// Provides the scene of the sample match.
+    ///
+    /// # Returns
+    ///
+    /// * Self - The built-in sample scene.
+    pub fn sample() -> Self {
+        Self::parse(SAMPLE).unwrap()
+    }
+
+    /// Parses a scene from the text of a scene file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The TOML text of the scene.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Scene, String>` - The scene, or an error message if it is not valid.
+    fn parse(text: &str) -> Result<Self, String> {
+        let scene: Self = toml::from_str(text).map_err(|e| e.to_string())?;
+        if let Some(game) = scene.game.as_ref() {
+            game.instance_id.parse::<InstanceId>()?;
+            game.region.parse::<Region>()?;
+            crate::parsers::parse_map(game.map.clone())
+                .ok_or_else(|| format!("unknown map: {}", game.map))?;
+        }
+        Ok(scene)
+    }
+
+    /// Creates the state and the actions of the scene at the moment of the screenshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The virtual time of the screenshot.
+    ///
+    /// # Returns
+    ///
+    /// * The state with the game of the scene and the actions the parsers would send during it.
+    pub fn build(&self, now: chrono::DateTime<chrono::Utc>) -> (Arc<StateHolder>, Vec<Action>) {
+        let ago = |seconds: i64| now - chrono::Duration::seconds(seconds);
+        let state = Arc::new(StateHolder::new());
+        let mut actions = Vec::new();
+
+        // The scene is checked when it is parsed
+        let mut game = self.game.as_ref().map(|scene| {
+            let joined = ago(scene.joined);
+            let mut clock = StormClock::default();
+            if let Some(uptime) = scene.uptime {
+                clock.add_handshake(joined, uptime);
+            }
+            let options = MatchOptions {
+                squad_size: scene.squad_size,
+                ..MatchOptions::default()
+            };
+            let mut game = Game::new(
+                scene.instance_id.parse().unwrap(),
+                scene.region.parse().unwrap(),
+                crate::parsers::parse_map(scene.map.clone()).unwrap(),
+                clock,
+                options,
+                None,
+                joined,
+            );
+            // The meteor showers are kept for the server, also from before the match was joined
+            for seconds in scene.meteors.iter() {
+                game.record_meteors(ago(*seconds));
+            }
+            state.set_game(game.clone());
+            actions.push(Action::UpdateState(events::UpdateState::new(Some(
+                game.clone(),
+            ))));
+            game
+        });
+
+        let timeline = Arc::new(EvacTimeline::from(&EvacConfig::default()));
+        let mut called = 0;
+        for action in self.actions.iter() {
+            match action {
+                SceneAction::TotalPlayers {
+                    ago: seconds,
+                    players,
+                } => {
+                    actions.push(Action::TotalPlayerCountUpdate(
+                        events::TotalPlayerCountUpdate::new(*players, ago(*seconds)),
+                    ));
+                }
+                SceneAction::NearPlayers {
+                    ago: seconds,
+                    players,
+                } => {
+                    if let Some(game) = game.as_mut() {
+                        game.near_players = *players;
+                        game.record_near_players(ago(*seconds));
+                    }
+                    actions.push(Action::NearPlayerCountUpdate(
+                        events::NearPlayerCountUpdate::new(*players, ago(*seconds)),
+                    ));
+                }
+                SceneAction::EvacCalled { ago: seconds, own } => {
+                    called += 1;
+                    let evac = Evac::new(called, None, *own, ago(*seconds), timeline.clone());
+                    actions.push(Action::EvacShipCalled(events::EvacShipCalled::new(evac)));
+                }
+                SceneAction::Meteors { ago: seconds } => {
+                    actions.push(Action::MeteorsEvent(events::MeteorsEvent::new(
+                        ago(*seconds),
+                        chrono::Duration::seconds(45),
+                        "Meteors event!".to_string(),
+                    )));
+                    if let Some(game) = game.as_mut() {
+                        game.record_meteors(ago(*seconds));
+                        actions.push(Action::UpdateState(events::UpdateState::new(Some(
+                            game.clone(),
+                        ))));
+                    }
+                }
+                SceneAction::Escaped { ago: seconds, own } => {
+                    actions.push(Action::PlayerEscaped(events::PlayerEscaped::new(
+                        ago(*seconds),
+                        chrono::Duration::seconds(15),
+                        "Player escaped".to_string(),
+                        *own,
+                    )));
+                }
+                SceneAction::Dead {
+                    ago: seconds,
+                    causer,
+                    weapon,
+                    damage,
+                    own,
+                } => {
+                    let death = events::PlayerDead::new(
+                        ago(*seconds),
+                        chrono::Duration::seconds(15),
+                        Actor::get(causer.clone()),
+                        0,
+                        weapon.as_deref().and_then(Weapon::get),
+                        *damage,
+                        *own,
+                    );
+                    actions.push(Action::PlayerDead(death.clone()));
+                    if let Some(game) = game.as_ref().filter(|_| *own) {
+                        let recap = events::DeathRecap::new(death, game);
+                        actions.push(Action::DeathRecap(recap));
+                    }
+                }
+                SceneAction::Leave => {
+                    state.leave_game();
+                    actions.push(Action::UpdateState(events::UpdateState::new(None)));
+                }
+            }
+        }
+
+        (state, actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_scene_is_valid() {
+        let now = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let (state, actions) = Scene::sample().build(now);
+        assert!(state.is_in_game());
+        assert!(matches!(actions.first(), Some(Action::UpdateState(_))));
+    }
+
+    #[test]
+    fn invalid_scenes_are_reported() {
+        let game = "[game]\ninstance_id = \"Prospect-1\"\nregion = \"eu\"\n";
+        assert!(Scene::parse(&format!("{}map = \"MAP99\"\n", game)).is_err());
+        assert!(Scene::parse("[[actions]]\ntype = \"dance\"\nago = 1\n").is_err());
+    }
+}
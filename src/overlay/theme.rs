@@ -0,0 +1,445 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the theme and the layout of the `Overlay`, which are built from
+//! the user configuration.
+
+use crate::config::{LayoutConfig, Preset, ThemeConfig};
+use crate::objects::Rarity;
+
+use egui::Color32;
+
+/// The `Colors` struct contains all colors used by the widgets (blocks) and events.
+#[derive(Debug, Clone)]
+pub struct Colors {
+    /// Server information and player counts.
+    pub server: Color32,
+    /// Warnings, e.g. the server is shutting down soon.
+    pub warning: Color32,
+    /// Time until the server shuts down.
+    pub server_death: Color32,
+    /// Accuracy of the storm clock with high confidence.
+    pub confidence_high: Color32,
+    /// Accuracy of the storm clock with medium confidence.
+    pub confidence_medium: Color32,
+    /// Accuracy of the storm clock with low or no confidence.
+    pub confidence_low: Color32,
+    /// Timers of the events.
+    pub timer: Color32,
+    /// Messages of the events.
+    pub event: Color32,
+    /// Messages of the events which are about to end.
+    pub event_inactive: Color32,
+    /// Items and actors of the common rarity.
+    pub common: Color32,
+    /// Items and actors of the uncommon rarity.
+    pub uncommon: Color32,
+    /// Items and actors of the rare rarity.
+    pub rare: Color32,
+    /// Items and actors of the epic rarity.
+    pub epic: Color32,
+    /// Items and actors of the exotic rarity.
+    pub exotic: Color32,
+    /// Items and actors of the legendary rarity.
+    pub legendary: Color32,
+    /// Items and actors of the rainbow rarity.
+    pub rainbow: Color32,
+}
+
+/// The `FontSizes` struct contains all font sizes used by the widgets (blocks) and events.
+#[derive(Debug, Clone)]
+pub struct FontSizes {
+    /// Player counts in the server block.
+    pub players: f32,
+    /// Server information in the server block.
+    pub server: f32,
+    /// Countdowns in the time block.
+    pub time: f32,
+    /// Events in the log block.
+    pub event: f32,
+    /// Secondary details, e.g. the instance ID or the accuracy of the storm clock.
+    pub detail: f32,
+}
+
+/// The `Theme` struct contains the colors, font sizes and spacing of the `Overlay`.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Colors of the labels.
+    pub colors: Colors,
+    /// Font sizes of the labels.
+    pub font_sizes: FontSizes,
+    /// Colors of the phases of the day cycle, the colors of the timing profile are used if empty.
+    pub phase_colors: Vec<Color32>,
+    /// The horizontal space between the labels of a block.
+    pub spacing: f32,
+}
+
+impl Theme {
+    /// Creates a theme from the preset.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset` - The predefined set of colors and font sizes.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Theme`.
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Default => Self {
+                colors: Colors {
+                    server: Color32::from_rgb(255, 128, 0),
+                    warning: Color32::RED,
+                    server_death: Color32::from_rgb(0x99, 0x66, 0x66),
+                    confidence_high: Color32::from_rgb(0x66, 0x99, 0x66),
+                    confidence_medium: Color32::from_rgb(0x99, 0x99, 0x66),
+                    confidence_low: Color32::from_rgb(0x99, 0x66, 0x66),
+                    timer: Color32::from_rgb(255, 175, 175),
+                    event: Color32::from_rgb(0, 255, 0),
+                    event_inactive: Color32::from_rgb(192, 192, 192),
+                    common: Color32::from_rgb(0x97, 0x9a, 0x9a),
+                    uncommon: Color32::from_rgb(0x58, 0xd6, 0x8d),
+                    rare: Color32::from_rgb(0x04, 0x95, 0xb4),
+                    epic: Color32::from_rgb(0xb5, 0x84, 0xc8),
+                    exotic: Color32::from_rgb(0xe7, 0x4c, 0x3c),
+                    legendary: Color32::from_rgb(0xff, 0x80, 0x80),
+                    rainbow: Color32::GOLD,
+                },
+                font_sizes: FontSizes {
+                    players: 40.0,
+                    server: 30.0,
+                    time: 28.0,
+                    event: 25.0,
+                    detail: 20.0,
+                },
+                phase_colors: Vec::new(),
+                spacing: 20.0,
+            },
+            // Based on the Okabe-Ito palette
+            Preset::Colorblind => Self {
+                colors: Colors {
+                    server: Color32::from_rgb(0xe6, 0x9f, 0x00),
+                    warning: Color32::from_rgb(0xd5, 0x5e, 0x00),
+                    server_death: Color32::from_rgb(0x99, 0x99, 0x99),
+                    confidence_high: Color32::from_rgb(0x00, 0x72, 0xb2),
+                    confidence_medium: Color32::from_rgb(0xf0, 0xe4, 0x42),
+                    confidence_low: Color32::from_rgb(0xd5, 0x5e, 0x00),
+                    timer: Color32::from_rgb(0xcc, 0x79, 0xa7),
+                    event: Color32::from_rgb(0x56, 0xb4, 0xe9),
+                    event_inactive: Color32::from_rgb(0x99, 0x99, 0x99),
+                    common: Color32::from_rgb(0xbb, 0xbb, 0xbb),
+                    uncommon: Color32::from_rgb(0x00, 0x9e, 0x73),
+                    rare: Color32::from_rgb(0x00, 0x72, 0xb2),
+                    epic: Color32::from_rgb(0xcc, 0x79, 0xa7),
+                    exotic: Color32::from_rgb(0xd5, 0x5e, 0x00),
+                    legendary: Color32::from_rgb(0xe6, 0x9f, 0x00),
+                    rainbow: Color32::from_rgb(0xf0, 0xe4, 0x42),
+                },
+                phase_colors: vec![
+                    Color32::from_rgb(0x56, 0xb4, 0xe9),
+                    Color32::from_rgb(0xf0, 0xe4, 0x42),
+                    Color32::from_rgb(0xe6, 0x9f, 0x00),
+                    Color32::from_rgb(0xcc, 0x79, 0xa7),
+                ],
+                ..Theme::preset(Preset::Default)
+            },
+            Preset::HighContrast => Self {
+                colors: Colors {
+                    server: Color32::from_rgb(255, 255, 0),
+                    warning: Color32::from_rgb(255, 0, 0),
+                    server_death: Color32::WHITE,
+                    confidence_high: Color32::from_rgb(0, 255, 0),
+                    confidence_medium: Color32::from_rgb(255, 255, 0),
+                    confidence_low: Color32::from_rgb(255, 0, 0),
+                    timer: Color32::from_rgb(0, 255, 255),
+                    event: Color32::WHITE,
+                    event_inactive: Color32::from_rgb(192, 192, 192),
+                    common: Color32::WHITE,
+                    uncommon: Color32::from_rgb(0, 255, 0),
+                    rare: Color32::from_rgb(0, 255, 255),
+                    epic: Color32::from_rgb(255, 0, 255),
+                    exotic: Color32::from_rgb(255, 0, 0),
+                    legendary: Color32::from_rgb(255, 128, 0),
+                    rainbow: Color32::from_rgb(255, 255, 0),
+                },
+                font_sizes: FontSizes {
+                    detail: 24.0,
+                    ..Theme::preset(Preset::Default).font_sizes
+                },
+                phase_colors: vec![
+                    Color32::from_rgb(0, 255, 255),
+                    Color32::from_rgb(255, 255, 0),
+                    Color32::WHITE,
+                    Color32::from_rgb(255, 0, 255),
+                ],
+                ..Theme::preset(Preset::Default)
+            },
+        }
+    }
+
+    /// Scales the font sizes and the spacing, e.g. for a monitor with a higher resolution.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The scale factor.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn scale(&mut self, factor: f32) {
+        let font_sizes = &mut self.font_sizes;
+        for size in [
+            &mut font_sizes.players,
+            &mut font_sizes.server,
+            &mut font_sizes.time,
+            &mut font_sizes.event,
+            &mut font_sizes.detail,
+        ] {
+            *size *= factor;
+        }
+        self.spacing *= factor;
+    }
+
+    /// Returns a copy of the theme with translucent colors, e.g. for fading labels.
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - The opacity from 0 to 1.
+    ///
+    /// # Returns
+    ///
+    /// * The faded theme.
+    pub fn faded(&self, opacity: f32) -> Self {
+        let mut theme = self.clone();
+        let colors = &mut theme.colors;
+        for color in [
+            &mut colors.server,
+            &mut colors.warning,
+            &mut colors.server_death,
+            &mut colors.confidence_high,
+            &mut colors.confidence_medium,
+            &mut colors.confidence_low,
+            &mut colors.timer,
+            &mut colors.event,
+            &mut colors.event_inactive,
+            &mut colors.common,
+            &mut colors.uncommon,
+            &mut colors.rare,
+            &mut colors.epic,
+            &mut colors.exotic,
+            &mut colors.legendary,
+            &mut colors.rainbow,
+        ]
+        .into_iter()
+        .chain(theme.phase_colors.iter_mut())
+        {
+            *color = color.linear_multiply(opacity);
+        }
+        theme
+    }
+
+    /// Returns the color of the rarity.
+    ///
+    /// # Arguments
+    ///
+    /// * `rarity` - The rarity of an item or actor.
+    ///
+    /// # Returns
+    ///
+    /// * A `Color32` of the rarity.
+    pub fn rarity(&self, rarity: &Rarity) -> Color32 {
+        match rarity {
+            Rarity::Common => self.colors.common,
+            Rarity::Uncommon => self.colors.uncommon,
+            Rarity::Rare => self.colors.rare,
+            Rarity::Epic => self.colors.epic,
+            Rarity::Exotic => self.colors.exotic,
+            Rarity::Legendary => self.colors.legendary,
+            Rarity::Rainbow => self.colors.rainbow,
+        }
+    }
+
+    /// Returns the color of the phase of the day cycle.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the phase in the timing profile.
+    /// * `color` - The color of the phase in the timing profile.
+    ///
+    /// # Returns
+    ///
+    /// * A `Color32` of the phase.
+    pub fn phase_color(&self, index: usize, color: Color32) -> Color32 {
+        self.phase_colors.get(index).copied().unwrap_or(color)
+    }
+
+    /// Returns a mutable reference to the color with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the color in the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the color, or None if there is no color with the name.
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color32> {
+        let colors = &mut self.colors;
+        let color = match name {
+            "server" => &mut colors.server,
+            "warning" => &mut colors.warning,
+            "server_death" => &mut colors.server_death,
+            "confidence_high" => &mut colors.confidence_high,
+            "confidence_medium" => &mut colors.confidence_medium,
+            "confidence_low" => &mut colors.confidence_low,
+            "timer" => &mut colors.timer,
+            "event" => &mut colors.event,
+            "event_inactive" => &mut colors.event_inactive,
+            "common" => &mut colors.common,
+            "uncommon" => &mut colors.uncommon,
+            "rare" => &mut colors.rare,
+            "epic" => &mut colors.epic,
+            "exotic" => &mut colors.exotic,
+            "legendary" => &mut colors.legendary,
+            "rainbow" => &mut colors.rainbow,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Returns a mutable reference to the font size with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the font size in the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the font size, or None if there is no font size with the name.
+    fn font_size_mut(&mut self, name: &str) -> Option<&mut f32> {
+        let font_sizes = &mut self.font_sizes;
+        let font_size = match name {
+            "players" => &mut font_sizes.players,
+            "server" => &mut font_sizes.server,
+            "time" => &mut font_sizes.time,
+            "event" => &mut font_sizes.event,
+            "detail" => &mut font_sizes.detail,
+            _ => return None,
+        };
+        Some(font_size)
+    }
+}
+
+impl Default for Theme {
+    /// Provides the theme with the original colors of the overlay.
+    fn default() -> Self {
+        Theme::preset(Preset::Default)
+    }
+}
+
+impl From<&ThemeConfig> for Theme {
+    /// Builds the theme from the preset and the overrides of the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The theme section of the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Theme`.
+    fn from(config: &ThemeConfig) -> Self {
+        let mut theme = Theme::preset(config.preset);
+
+        // Unknown names are reported, but don't prevent the overlay from starting
+        for (name, color) in &config.colors {
+            match theme.color_mut(name) {
+                Some(target) => *target = (*color).into(),
+                None => log::error!("Unknown theme color: {:?}", name),
+            }
+        }
+        for (name, size) in &config.font_sizes {
+            match theme.font_size_mut(name) {
+                Some(target) if *size > 0.0 => *target = *size,
+                Some(_) => log::error!("Invalid font size of {:?}: {}", name, size),
+                None => log::error!("Unknown theme font size: {:?}", name),
+            }
+        }
+        if !config.phase_colors.is_empty() {
+            theme.phase_colors = config
+                .phase_colors
+                .iter()
+                .map(|color| (*color).into())
+                .collect();
+        }
+        if let Some(spacing) = config.spacing {
+            theme.spacing = spacing;
+        }
+
+        theme
+    }
+}
+
+/// The `Layout` struct contains the placement and order of the blocks of the `Overlay`.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    /// The corner or side of the screen the overlay is attached to.
+    pub anchor: egui::Align2,
+    /// The offset of the overlay from the anchor.
+    pub offset: egui::Vec2,
+    /// The vertical space between the blocks.
+    pub block_spacing: f32,
+    /// The names of the displayed blocks in their order.
+    pub blocks: Vec<String>,
+}
+
+impl From<&LayoutConfig> for Layout {
+    /// Builds the layout from the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The layout section of the configuration.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Layout`.
+    fn from(config: &LayoutConfig) -> Self {
+        Self {
+            anchor: config.anchor.into(),
+            offset: egui::Vec2::new(config.offset[0], config.offset[1]),
+            block_spacing: config.block_spacing,
+            blocks: config.blocks.iter().map(|b| b.to_lowercase()).collect(),
+        }
+    }
+}
+
+impl Layout {
+    /// Scales the offset and the spacing, e.g. for a monitor with a higher resolution.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The scale factor.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn scale(&mut self, factor: f32) {
+        self.offset *= factor;
+        self.block_spacing *= factor;
+    }
+}
@@ -21,6 +21,8 @@
 //! This module contains implementation of the `Parser` trait to search for events in the game log
 //! related to the environment events in game, such as evacuation ship called or meteor event
 
+use crate::config::{EvacConfig, LogLine};
+use crate::objects::{Evac, EvacTimeline};
 use crate::overlay::events;
 use crate::state::StateHolder;
 
@@ -29,12 +31,175 @@ use winit::event_loop::EventLoopProxy;
 use std::sync;
 
 /// Struct that parses game events.
-pub struct Parser;
+pub struct Parser {
+    /// The phases of the evac ships.
+    timeline: sync::Arc<EvacTimeline>,
+    /// The log line which marks the call of an evac ship by the player.
+    own_call: Option<LogLine>,
+    /// The longest time between the call of the player and the evac ship.
+    own_call_window: chrono::Duration,
+    /// The ID of the game instance the evac ships belong to.
+    instance_id: Option<String>,
+    /// The evac ships of the current game which haven't left yet.
+    evacs: Vec<Evac>,
+    /// The number of evac ships called in the current game.
+    called: usize,
+    /// The time of the last call of the player which wasn't matched to an evac ship yet.
+    pending_own_call: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Parser {
+    /// Constructs a new Parser object.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The timeline of the evac ships and the log lines they are tracked by.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Parser`.
+    pub fn new(config: &EvacConfig) -> Self {
+        // The default configuration has no log lines, as they differ between the builds
+        if config.phases.iter().all(|phase| phase.contains.is_none()) {
+            log::warn!("No log lines of the evac phases are configured, tracking by timers only");
+        }
+        if config.own_call.is_none() {
+            log::warn!("The `own_call` log line is not configured, no evac ship is marked own");
+        }
+
+        Self {
+            timeline: sync::Arc::new(EvacTimeline::from(config)),
+            own_call: config.own_call.clone(),
+            own_call_window: chrono::Duration::seconds(config.own_call_window as i64),
+            instance_id: None,
+            evacs: Vec::new(),
+            called: 0,
+            pending_own_call: None,
+        }
+    }
+
+    /// Starts counting the evac ships again when another game is joined, and forgets the evac
+    /// ships which have left.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - A reference to an instance of `StateHolder`.
+    /// * `time` - The time of the log line.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn refresh(&mut self, state: &StateHolder, time: chrono::DateTime<chrono::Utc>) {
+        let instance_id = state
+            .get_games()
+            .lock()
+            .unwrap()
+            .front()
+            .map(|game| game.instance_id.to_string());
+        if instance_id != self.instance_id {
+            self.instance_id = instance_id;
+            self.evacs.clear();
+            self.called = 0;
+            self.pending_own_call = None;
+        }
+        self.evacs.retain(|evac| evac.leaves_at() > time);
+    }
+
+    /// Marks the evac ship called by the player, or remembers the call until the evac ship spawns.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the call.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the number of the evac ship, or None if it hasn't spawned yet.
+    fn own_call(&mut self, time: chrono::DateTime<chrono::Utc>) -> Option<usize> {
+        // The latest evac ship which spawned shortly before the call
+        let window = self.own_call_window;
+        let evac = self
+            .evacs
+            .iter_mut()
+            .rev()
+            .find(|evac| !evac.own && time - evac.called_at <= window);
+        match evac {
+            Some(evac) => {
+                evac.own = true;
+                Some(evac.sequence)
+            }
+            None => {
+                self.pending_own_call = Some(time);
+                None
+            }
+        }
+    }
+
+    /// Adds the evac ship whose call is logged by a log line.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the log line.
+    /// * `time` - The time of the log line.
+    ///
+    /// # Returns
+    ///
+    /// * The evac ship with the next number in the game.
+    fn call(&mut self, text: &str, time: chrono::DateTime<chrono::Utc>) -> Evac {
+        // The actor tells the evac ships apart in the following lines
+        let actor = text
+            .trim_start_matches("Warning: ")
+            .split_whitespace()
+            .next()
+            .map(|actor| actor.trim_end_matches(|c: char| !c.is_alphanumeric()))
+            .map(str::to_string);
+        let own = self
+            .pending_own_call
+            .take()
+            .is_some_and(|call| time - call <= self.own_call_window);
+        self.called += 1;
+        let evac = Evac::new(self.called, actor, own, time, self.timeline.clone());
+        self.evacs.push(evac.clone());
+        evac
+    }
 
-impl Default for Parser {
-    /// Constructs a default Parser object.
-    fn default() -> Self {
-        Self
+    /// Finds the evac ship and the phase marked by a log line.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the log line.
+    /// * `time` - The time of the log line.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the number of the evac ship and the index of the phase, or None if the line is
+    ///   not an evidence of a phase.
+    fn advance(
+        &mut self,
+        text: &str,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> Option<(usize, usize)> {
+        let phase = self.timeline.phases.iter().position(|phase| {
+            phase
+                .evidence
+                .as_ref()
+                .is_some_and(|evidence| text.contains(evidence.as_str()))
+        })?;
+
+        // The evac ship named in the line, otherwise the oldest one which hasn't reached the phase
+        let named = self.evacs.iter().position(|evac| {
+            evac.actor
+                .as_ref()
+                .is_some_and(|actor| text.contains(actor.as_str()))
+        });
+        let index = named.or_else(|| {
+            self.evacs
+                .iter()
+                .position(|evac| evac.confirmed_phase() < phase)
+        })?;
+
+        let evac = &mut self.evacs[index];
+        evac.advance(phase, time);
+        Some((evac.sequence, phase))
     }
 }
 
@@ -61,26 +226,58 @@ impl super::Parser for Parser {
         text: &str,
         event_loop_proxy: sync::Arc<sync::Mutex<EventLoopProxy<events::Action>>>,
     ) {
-        // If the event is of type "LogYActivities" and the game is in progress
-        if type_ == "LogYActivities" && state.is_in_game() {
+        // If the game is not in progress, return early
+        if !state.is_in_game() {
+            return;
+        }
+        self.refresh(&state, time);
+
+        // The call of the player may be logged in any category
+        let own_call = self
+            .own_call
+            .as_ref()
+            .is_some_and(|line| line.category == type_ && text.contains(&line.contains));
+        if own_call {
+            if let Some(sequence) = self.own_call(time) {
+                let sender = event_loop_proxy.lock().unwrap();
+                sender
+                    .send_event(events::Action::EvacUpdate(events::EvacUpdate::new(
+                        sequence, None, true, time,
+                    )))
+                    .unwrap();
+                log::info!("Evac ship #{} called by the player", sequence);
+            }
+        }
+
+        // If the event is of type "LogYActivities"
+        if type_ == "LogYActivities" {
             match text {
                 // If the event indicates the evacuation ship being called
                 t if t.starts_with("Warning: AC_EvacShip_BP") => {
+                    let evac = self.call(t, time);
+
                     // Lock the event loop proxy and send a `EvacShipCalled` event for `Overlay`
                     let sender = event_loop_proxy.lock().unwrap();
                     sender
                         .send_event(events::Action::EvacShipCalled(events::EvacShipCalled::new(
-                            time,
-                            chrono::Duration::seconds(86),
-                            "Evac ship [called]".to_string(),
+                            evac,
                         )))
                         .unwrap();
 
                     // Log the event
-                    log::info!("Evac ship called");
+                    log::info!("Evac ship #{} called", self.called);
                 }
                 // If the event indicates the start of a meteor shower
                 t if t.starts_with("Warning: AA_MeteorShowerSpawner") => {
+                    // The meteor showers are counted per server. The games are locked before the
+                    // event loop proxy, in the same order as everywhere else
+                    let game = state.get_games().lock().unwrap().front_mut().map(|game| {
+                        let count = game.record_meteors(time);
+                        // Log the event
+                        log::info!("Meteors event #{} on the server", count);
+                        game.clone()
+                    });
+
                     // Lock the event loop proxy and send a `MeteorsEvent` event for `Overlay`
                     let sender = event_loop_proxy.lock().unwrap();
                     sender
@@ -90,12 +287,79 @@ impl super::Parser for Parser {
                             "Meteors event!".to_string(),
                         )))
                         .unwrap();
-
-                    // Log the event
-                    log::info!("Meteors event!")
+                    if let Some(game) = game {
+                        sender
+                            .send_event(events::Action::UpdateState(events::UpdateState::new(
+                                Some(game),
+                            )))
+                            .unwrap();
+                    }
+                }
+                // If the event marks a phase of an evac ship
+                t => {
+                    if let Some((sequence, phase)) = self.advance(t, time) {
+                        let sender = event_loop_proxy.lock().unwrap();
+                        sender
+                            .send_event(events::Action::EvacUpdate(events::EvacUpdate::new(
+                                sequence,
+                                Some(phase),
+                                false,
+                                time,
+                            )))
+                            .unwrap();
+                        log::info!(
+                            "Evac ship #{} {}",
+                            sequence,
+                            self.timeline.phases[phase].name
+                        );
+                    }
                 }
-                _ => (),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(seconds: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    /// Provides the parser with the default timeline, the landing marked by a log line.
+    fn parser() -> Parser {
+        let mut config = EvacConfig::default();
+        config.phases[2].contains = Some("landed".to_string());
+        Parser::new(&config)
+    }
+
+    #[test]
+    fn overlapping_evacs_are_numbered_apart() {
+        let mut parser = parser();
+        let first = parser.call("Warning: AC_EvacShip_BP_C_1: called", time(0));
+        let second = parser.call("Warning: AC_EvacShip_BP_C_2: called", time(20));
+        assert_eq!((first.sequence, second.sequence), (1, 2));
+        assert_eq!(first.actor.as_deref(), Some("AC_EvacShip_BP_C_1"));
+        assert_eq!(second.actor.as_deref(), Some("AC_EvacShip_BP_C_2"));
+    }
+
+    #[test]
+    fn log_lines_advance_the_phase_before_the_timer() {
+        let mut parser = parser();
+        parser.call("Warning: AC_EvacShip_BP_C_1: called", time(0));
+        parser.call("Warning: AC_EvacShip_BP_C_2: called", time(20));
+
+        // The second evac ship lands 30 seconds early, the first one keeps its timer
+        let line = "AC_EvacShip_BP_C_2: landed";
+        assert_eq!(parser.advance(line, time(37)), Some((2, 2)));
+        let (first, second) = (&parser.evacs[0], &parser.evacs[1]);
+        assert_eq!(first.phase_at(time(37)).unwrap().0.name, "arriving");
+        assert_eq!(second.phase_at(time(37)).unwrap().0.name, "landed");
+        assert_eq!(second.leaves_at(), time(37 + 29 + 10));
+
+        // A line without the actor goes to the oldest evac ship which hasn't landed
+        assert_eq!(parser.advance("landed", time(40)), Some((1, 2)));
+        assert_eq!(parser.advance("landed", time(41)), None);
+    }
+}
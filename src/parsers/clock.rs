@@ -0,0 +1,116 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains implementation of the `Parser` trait to search for log lines which mark
+//! the beginning of a phase of the day cycle, such as weather or time of day changes, and to
+//! anchor the storm clock of the current game to them.
+
+use crate::config::ClockAnchor;
+use crate::overlay::events;
+use crate::state::StateHolder;
+
+use winit::event_loop::EventLoopProxy;
+
+use std::sync;
+
+/// Struct that parses storm clock anchors.
+pub struct Parser {
+    /// Log lines which mark the beginning of a phase, from the configuration.
+    anchors: Vec<ClockAnchor>,
+}
+
+impl Parser {
+    /// Constructs a new Parser object.
+    ///
+    /// # Arguments
+    ///
+    /// * `anchors` - Log lines which mark the beginning of a phase.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Parser`.
+    pub fn new(anchors: Vec<ClockAnchor>) -> Self {
+        Self { anchors }
+    }
+}
+
+impl super::Parser for Parser {
+    /// Parse a game instance's event log from file and update the state accordingly.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - A reference to an instance of `StateHolder` shared among multiple threads.
+    /// * `time` - Current UTC timestamp according the log information
+    /// * `type_` - A string reference representing type of event
+    /// * `text` - A string reference representing text to parse.
+    /// * `event_loop_proxy` - A reference to an instance of `EventLoopProxy` shared among multiple
+    ///   threads, allowing safe mutation.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    fn parse(
+        &mut self,
+        state: sync::Arc<StateHolder>,
+        time: chrono::DateTime<chrono::Utc>,
+        type_: &str,
+        text: &str,
+        event_loop_proxy: sync::Arc<sync::Mutex<EventLoopProxy<events::Action>>>,
+    ) {
+        // If game is not in progress, return early
+        if !state.is_in_game() {
+            return;
+        }
+
+        // Find the anchor which matches the log line
+        let anchor = self
+            .anchors
+            .iter()
+            .find(|anchor| anchor.category == type_ && text.contains(&anchor.contains));
+
+        if let Some(anchor) = anchor {
+            if let Some(game) = state.get_games().lock().unwrap().front_mut() {
+                // Anchor the storm clock of the current game
+                let timings = game.map.timings().clone();
+                if game.clock.add_phase(time, &anchor.phase, &timings) {
+                    log::info!(
+                        "Storm clock anchored to {:?}, confidence {:?}",
+                        anchor.phase,
+                        game.clock.confidence()
+                    );
+
+                    // Send an update to the game state in `Overlay`
+                    let sender = event_loop_proxy.lock().unwrap();
+                    sender
+                        .send_event(events::Action::UpdateState(events::UpdateState::new(Some(
+                            game.clone(),
+                        ))))
+                        .unwrap();
+                } else {
+                    log::error!(
+                        "Unknown phase {:?} for timing profile {:?}",
+                        anchor.phase,
+                        timings.name
+                    );
+                }
+            }
+        }
+    }
+}
@@ -34,6 +34,8 @@ pub struct Listener {
     state: sync::Arc<StateHolder>,
     /// Regex pattern to match each line of log.
     line_pattern: regex::Regex,
+    /// Regex pattern to match lines of the log header, which are written without a timestamp.
+    header_pattern: regex::Regex,
     /// Format of the datetime in log.
     format: String,
     /// Collection of parsers.
@@ -46,22 +48,34 @@ impl Listener {
     /// # Arguments
     ///
     /// * `state` - A shared reference to an instance of `StateHolder`.
+    /// * `config` - The user configuration.
+    /// * `audio` - The audio service which plays the alerts.
     ///
     /// # Return
     ///
     /// This function will return an instance of `Listener`.
-    pub fn new(state: sync::Arc<StateHolder>) -> Self {
+    pub fn new(
+        state: sync::Arc<StateHolder>,
+        config: &crate::config::Config,
+        audio: crate::audio::Audio,
+    ) -> Self {
         Self {
             state,
             line_pattern: regex::Regex::new(
                 r"\[(\d{4}\.\d{2}\.\d{2}-\d{2}\.\d{2}\.\d{2}:\d{3})]\[.{3}](\w*): (.*)",
             )
             .unwrap(),
+            header_pattern: regex::Regex::new(r"^(\w+): (.*)").unwrap(),
             format: String::from("%Y.%m.%d-%H.%M.%S:%3f"),
             parsers: vec![
-                Box::new(super::activities::Parser::default()),
-                Box::new(super::player::Parser::default()),
+                Box::new(super::activities::Parser::new(&config.evac)),
+                Box::new(super::player::Parser::new(
+                    audio,
+                    &config.proximity,
+                    &config.kill_feed,
+                )),
                 Box::new(super::server::Parser::default()),
+                Box::new(super::clock::Parser::new(config.clock.anchors.clone())),
             ],
         }
     }
@@ -82,26 +96,72 @@ impl Listener {
         string: &str,
         event_loop_proxy: sync::Arc<sync::Mutex<winit::event_loop::EventLoopProxy<events::Action>>>,
     ) {
+        match self.split(string) {
+            Some((Some(time), type_, text)) => {
+                // The build may also be written after the header
+                self.detect_build(type_, text);
+
+                // Parse the captured data with all parsers
+                for parser in self.parsers.iter_mut() {
+                    parser.parse(
+                        self.state.clone(),
+                        time,
+                        type_,
+                        text,
+                        event_loop_proxy.clone(),
+                    );
+                }
+            }
+            // Lines of the header are only used to detect the build of the game
+            Some((None, type_, text)) => self.detect_build(type_, text),
+            None => (),
+        }
+    }
+
+    /// Splits a line of the log into its timestamp, type and text.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The line of the log.
+    ///
+    /// # Return
+    ///
+    /// This function will return the timestamp, `None` for the lines of the header, with the type
+    /// and the text of the line, or `None` if the line has neither form.
+    fn split<'a>(
+        &self,
+        string: &'a str,
+    ) -> Option<(Option<chrono::DateTime<chrono::Utc>>, &'a str, &'a str)> {
         // Attempt to capture groups in the line with the defined regex pattern
         if let Some(captures) = self.line_pattern.captures(string) {
             // Extract and parse the timestamp
             let time = chrono::Utc
                 .datetime_from_str(&captures[1], &self.format)
                 .unwrap();
+            let (type_, text) = (captures.get(2)?.as_str(), captures.get(3)?.as_str());
+            Some((Some(time), type_, text))
+        } else {
+            let captures = self.header_pattern.captures(string)?;
+            Some((None, captures.get(1)?.as_str(), captures.get(2)?.as_str()))
+        }
+    }
 
-            // Extract the type and text from the captures
-            let type_ = &captures[2];
-            let text = &captures[3];
-
-            // Parse the captured data with all parsers
-            for parser in self.parsers.iter_mut() {
-                parser.parse(
-                    self.state.clone(),
-                    time,
-                    type_,
-                    text,
-                    event_loop_proxy.clone(),
-                );
+    /// Detects the build of the game client and stores it in the state.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_` - The type of the log line.
+    /// * `text` - The text of the log line.
+    ///
+    /// # Return
+    ///
+    /// * None
+    fn detect_build(&self, type_: &str, text: &str) {
+        if let Some(build) = crate::objects::GameBuild::from_log(type_, text) {
+            // The same build is written several times, so log only changes
+            if self.state.get_build().map(|b| b.changelist) != Some(build.changelist) {
+                log::info!("Game build: {} ({})", build, build.version);
+                self.state.set_build(build);
             }
         }
     }
@@ -151,3 +211,41 @@ impl Listener {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listener() -> Listener {
+        let state = sync::Arc::new(StateHolder::new());
+        Listener::new(
+            state,
+            &crate::config::Config::default(),
+            crate::audio::Audio::silent(),
+        )
+    }
+
+    #[test]
+    fn builds_are_detected_with_and_without_timestamp() {
+        for line in [
+            "LogInit: Build: ++Prospect+Release-CL-123456",
+            "[2023.06.01-12.00.00:000][  0]LogInit: Build: ++Prospect+Release-CL-123456",
+        ] {
+            let listener = listener();
+            let (_, type_, text) = listener.split(line).unwrap();
+            listener.detect_build(type_, text);
+            let build = listener.state.get_build().unwrap();
+            assert_eq!(build.changelist, 123456, "{}", line);
+        }
+    }
+
+    #[test]
+    fn header_lines_have_no_timestamp() {
+        let listener = listener();
+        let line = "[2023.06.01-12.00.00:000][  0]LogInit: Build: ++Prospect+Release-CL-1";
+        assert!(listener.split(line).unwrap().0.is_some());
+        let header = "LogInit: Build: ++Prospect+Release-CL-1";
+        assert!(listener.split(header).unwrap().0.is_none());
+        assert!(listener.split("Log file open, 06/01/23 12:00:00").is_none());
+    }
+}
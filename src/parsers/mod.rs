@@ -21,11 +21,14 @@
 //! This module contains `Parser` trait and helper function to parse text from game log.
 
 mod activities;
+mod clock;
 mod listener;
 mod player;
+mod proximity;
 mod server;
 
 pub use listener::Listener;
+pub use server::parse_map;
 
 /// Represents a trait for parsing of game log functionalities
 trait Parser {
@@ -21,8 +21,11 @@
 //! This module contains implementation of the `Parser` trait to search for events in the game log
 //! related to the players activity
 
+use super::proximity::Proximity;
 use super::substring_between;
-use crate::objects::{Actor, Weapon};
+use crate::audio::{Alert, Audio};
+use crate::config::{KillFeedConfig, ProximityConfig};
+use crate::objects::{Actor, ActorInstanceId, Weapon};
 use crate::overlay::events;
 use crate::state::StateHolder;
 
@@ -34,15 +37,53 @@ use std::sync;
 pub struct Parser {
     /// Boolean indicating if the last game has finished.
     last_finished: bool,
+    /// The audio service which plays the alerts.
+    audio: Audio,
+    /// The rules of the alerts about nearby players.
+    proximity: Proximity,
+    /// The text that the log lines of the results of the player contain, if it is configured.
+    own_death: Option<String>,
 }
 
-impl Default for Parser {
-    /// Constructs a new Parser object with default value.
-    fn default() -> Self {
+impl Parser {
+    /// Constructs a new Parser object.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio` - The audio service which plays the alerts.
+    /// * `proximity` - The rules of the alerts about nearby players.
+    /// * `kill_feed` - The settings of the kill feed, which tell the deaths of the player apart.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Parser`.
+    pub fn new(audio: Audio, proximity: &ProximityConfig, kill_feed: &KillFeedConfig) -> Self {
+        if kill_feed.own_death.is_none() {
+            log::warn!("`own_death` of the kill feed is not set, no death is taken as own");
+        }
+
         Self {
             last_finished: false,
+            audio,
+            proximity: Proximity::new(proximity),
+            own_death: kill_feed.own_death.clone(),
         }
     }
+
+    /// Checks whether the result of a match is the player's.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The log line of the result.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the line contains the configured text, never without it.
+    fn is_own(&self, text: &str) -> bool {
+        self.own_death
+            .as_ref()
+            .is_some_and(|own_death| text.contains(own_death.as_str()))
+    }
 }
 
 impl super::Parser for Parser {
@@ -90,9 +131,9 @@ impl super::Parser for Parser {
                                 if player_state == "inMatch" {
                                     // Increase number of total players in current game
                                     game.total_players += 1;
-                                    // Issue beep to notify user about this event
+                                    // Play an alert to notify user about this event
                                     if game.total_players > game.party_size {
-                                        crate::utils::beep(2000, 250, time);
+                                        self.audio.play(Alert::PlayerJoined, time);
                                     }
                                     self.last_finished = false;
                                 } else {
@@ -100,9 +141,9 @@ impl super::Parser for Parser {
                                     if game.total_players > 0 {
                                         // Decrease number of total players in current game
                                         game.total_players -= 1;
-                                        // Issue beep to notify user about this event
+                                        // Play an alert to notify user about this event
                                         if game.total_players > game.party_size {
-                                            crate::utils::beep(400, 150, time);
+                                            self.audio.play(Alert::PlayerLeft, time);
                                         }
                                         self.last_finished = true;
                                     }
@@ -111,7 +152,10 @@ impl super::Parser for Parser {
                                 let sender = event_loop_proxy.lock().unwrap();
                                 sender
                                     .send_event(events::Action::TotalPlayerCountUpdate(
-                                        events::TotalPlayerCountUpdate::new(game.total_players),
+                                        events::TotalPlayerCountUpdate::new(
+                                            game.total_players,
+                                            time,
+                                        ),
                                     ))
                                     .unwrap();
                             } else {
@@ -122,30 +166,45 @@ impl super::Parser for Parser {
                         t if t.starts_with("OnPlayerStateChanged") => {
                             // Increase the number of near players
                             game.near_players += 1;
+                            game.record_near_players(time);
                             // Send an event to update the near player count in 'Overlay`
                             let sender = event_loop_proxy.lock().unwrap();
                             sender
                                 .send_event(events::Action::NearPlayerCountUpdate(
-                                    events::NearPlayerCountUpdate::new(game.near_players),
+                                    events::NearPlayerCountUpdate::new(game.near_players, time),
                                 ))
                                 .unwrap();
+                            // Alert about the players coming near
+                            if let Some(alert) = self.proximity.update(game, time) {
+                                self.audio.play(alert, time);
+                                sender
+                                    .send_event(events::Action::ProximityAlert(
+                                        events::ProximityAlert::new(time),
+                                    ))
+                                    .unwrap();
+                            }
                         }
                         // When a near enemy player's character is destroyed
                         t if t.starts_with("AYPlayerCharacter::Destroyed()") => {
                             // Decrease the number of near players
                             if game.near_players > 0 {
                                 game.near_players -= 1;
+                                game.record_near_players(time);
+                                // Players going away are counted by the proximity alerts too
+                                self.proximity.update(game, time);
                                 // Send an event to update the near player count in 'Overlay`
                                 let sender = event_loop_proxy.lock().unwrap();
                                 sender
                                     .send_event(events::Action::NearPlayerCountUpdate(
-                                        events::NearPlayerCountUpdate::new(game.near_players),
+                                        events::NearPlayerCountUpdate::new(game.near_players, time),
                                     ))
                                     .unwrap();
                             }
                         }
                         // When a player's match finishes with a result
                         t if t.starts_with("AYPlayerState::OnRep_PlayerMatchFinishedResult") => {
+                            // Without the configured text no result is the player's
+                            let own = self.is_own(text);
                             // Handle match result (escaped, dead)
                             if let Some(result) = substring_between(text, "Result:", " ") {
                                 match result.to_lowercase().as_str() {
@@ -158,6 +217,7 @@ impl super::Parser for Parser {
                                                     time,
                                                     chrono::Duration::seconds(15),
                                                     "Player escaped".to_string(),
+                                                    own,
                                                 ),
                                             ))
                                             .unwrap();
@@ -166,12 +226,21 @@ impl super::Parser for Parser {
                                     // If the player died
                                     "dead" => {
                                         // Handling death cause and damage here
-                                        let causer_parts =
-                                            substring_between(text, "Damage:Causer:", " ").unwrap();
-                                        let causer_parts_spited: Vec<&str> =
-                                            causer_parts.split("_C_").collect();
-                                        let causer_string = causer_parts_spited[0].to_string();
-                                        let causer = Actor::get(causer_string);
+                                        let causer_string =
+                                            substring_between(text, "Damage:Causer:", " ")
+                                                .unwrap_or_default();
+                                        // Count the kill if the causer is an actor instance
+                                        let (causer, causer_kills) =
+                                            match causer_string.parse::<ActorInstanceId>() {
+                                                Ok(causer_id) => (
+                                                    Actor::get(causer_id.class().to_string()),
+                                                    game.kill(causer_id),
+                                                ),
+                                                Err(e) => {
+                                                    log::warn!("{}", e);
+                                                    (Actor::get(causer_string), 0)
+                                                }
+                                            };
 
                                         let origin_string =
                                             substring_between(text, "Origin:OriginRow:[", "]");
@@ -188,8 +257,6 @@ impl super::Parser for Parser {
                                                 .parse::<f32>()
                                                 .unwrap();
 
-                                        let causer_kills =
-                                            game.kill(causer_parts_spited[1].to_string());
                                         let weapon =
                                             causer.clone().and_then(|c| match c.name.as_str() {
                                                 "None" => Weapon::get("Suicide"),
@@ -215,19 +282,27 @@ impl super::Parser for Parser {
                                         log::info!("----- Causer kills {:?} times", causer_kills);
 
                                         // Send an 'Overlay` event to indicate that the player has died
+                                        let death = events::PlayerDead::new(
+                                            time,
+                                            chrono::Duration::seconds(15),
+                                            causer,
+                                            causer_kills,
+                                            weapon,
+                                            damage,
+                                            own,
+                                        );
                                         let sender = event_loop_proxy.lock().unwrap();
                                         sender
-                                            .send_event(events::Action::PlayerDead(
-                                                events::PlayerDead::new(
-                                                    time,
-                                                    chrono::Duration::seconds(15),
-                                                    causer,
-                                                    causer_kills,
-                                                    weapon,
-                                                    damage,
-                                                ),
-                                            ))
+                                            .send_event(events::Action::PlayerDead(death.clone()))
                                             .unwrap();
+                                        // The recap of the death of the player stays until it is
+                                        // dismissed
+                                        if own {
+                                            let recap = events::DeathRecap::new(death, game);
+                                            sender
+                                                .send_event(events::Action::DeathRecap(recap))
+                                                .unwrap();
+                                        }
                                     }
                                     _ => {
                                         log::error!("Unknown result: {}", text.to_string());
@@ -256,7 +331,7 @@ impl super::Parser for Parser {
                         let sender = event_loop_proxy.lock().unwrap();
                         sender
                             .send_event(events::Action::TotalPlayerCountUpdate(
-                                events::TotalPlayerCountUpdate::new(game.total_players),
+                                events::TotalPlayerCountUpdate::new(game.total_players, time),
                             ))
                             .unwrap();
                         log::info!("Player finished before loading, revert player count.");
@@ -267,3 +342,30 @@ impl super::Parser for Parser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEATH: &str = "AYPlayerState::OnRep_PlayerMatchFinishedResult Player:YourName \
+        Result:Dead Damage:Causer:None m_healthDamage:100.0 ";
+
+    fn parser(own_death: Option<&str>) -> Parser {
+        let kill_feed = KillFeedConfig {
+            own_death: own_death.map(str::to_string),
+            ..KillFeedConfig::default()
+        };
+        Parser::new(Audio::silent(), &ProximityConfig::default(), &kill_feed)
+    }
+
+    #[test]
+    fn deaths_are_not_own_without_own_death() {
+        assert!(!parser(None).is_own(DEATH));
+    }
+
+    #[test]
+    fn deaths_are_own_by_own_death() {
+        assert!(parser(Some("YourName")).is_own(DEATH));
+        assert!(!parser(Some("OtherName")).is_own(DEATH));
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This module contains the rules of the alerts about the players coming near.
+
+use crate::audio::Alert;
+use crate::config::ProximityConfig;
+use crate::objects::Game;
+
+/// The `Proximity` struct decides when the players coming near are worth an alert.
+pub struct Proximity {
+    /// The rules of the alerts.
+    config: ProximityConfig,
+    /// The ID of the game instance the contacts belong to.
+    instance_id: Option<String>,
+    /// The number of nearby players outside the party at the last alert, zero before the first
+    /// contact.
+    alerted: usize,
+    /// The time of the last alert in the game log.
+    last_alert: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Proximity {
+    /// Creates a new `Proximity` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The rules of the alerts.
+    ///
+    /// # Returns
+    ///
+    /// * Self - A new instance of `Proximity`.
+    pub fn new(config: &ProximityConfig) -> Self {
+        Self {
+            config: config.clone(),
+            instance_id: None,
+            alerted: 0,
+            last_alert: None,
+        }
+    }
+
+    /// Checks the number of nearby players after it has changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `game` - The current game with the number of nearby players and the size of the party.
+    /// * `time` - The time of the change in the game log.
+    ///
+    /// # Returns
+    ///
+    /// * Some with the alert, or None if the change is not worth an alert.
+    pub fn update(&mut self, game: &Game, time: chrono::DateTime<chrono::Utc>) -> Option<Alert> {
+        // The contacts of the previous game don't count
+        let instance_id = game.instance_id.to_string();
+        if self.instance_id.as_ref() != Some(&instance_id) {
+            self.instance_id = Some(instance_id);
+            self.alerted = 0;
+        }
+        if !self.config.enabled {
+            return None;
+        }
+
+        // The nearby players include the teammates
+        let contacts = game
+            .near_players
+            .saturating_sub(game.party_size.saturating_sub(1));
+        if contacts < self.config.threshold.max(1) {
+            self.alerted = 0;
+            return None;
+        }
+        let alert = if self.alerted == 0 {
+            Alert::FirstContact
+        } else if contacts >= self.alerted + self.config.step.max(1) {
+            Alert::MoreContacts
+        } else {
+            // Players going away lower the level the next contact is counted from
+            self.alerted = self.alerted.min(contacts);
+            return None;
+        };
+        self.alerted = contacts;
+
+        let cooldown = chrono::Duration::seconds(self.config.cooldown as i64);
+        if self.last_alert.is_some_and(|last| time - last < cooldown) {
+            return None;
+        }
+        self.last_alert = Some(time);
+        Some(alert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{GameMap, InstanceId, MatchOptions, Region, StormClock, Timings};
+
+    fn time(seconds: i64) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn game(instance_id: &str, party_size: usize) -> Game {
+        let mut game = Game::new(
+            instance_id.parse::<InstanceId>().unwrap(),
+            Region::Europe,
+            GameMap::BrightSands(Timings::for_map("Map01")),
+            StormClock::default(),
+            MatchOptions::default(),
+            None,
+            time(0),
+        );
+        game.party_size = party_size;
+        game
+    }
+
+    /// Sets the number of nearby players and checks it at the moment.
+    fn near(
+        proximity: &mut Proximity,
+        game: &mut Game,
+        players: usize,
+        seconds: i64,
+    ) -> Option<Alert> {
+        game.near_players = players;
+        proximity.update(game, time(seconds))
+    }
+
+    #[test]
+    fn alerts_first_contact_and_more_contacts() {
+        let mut proximity = Proximity::new(&ProximityConfig::default());
+        let mut game = game("Prospect-1", 1);
+        assert_eq!(
+            near(&mut proximity, &mut game, 1, 0),
+            Some(Alert::FirstContact)
+        );
+        assert_eq!(
+            near(&mut proximity, &mut game, 2, 10),
+            Some(Alert::MoreContacts)
+        );
+        // Nobody near resets the contact
+        assert_eq!(near(&mut proximity, &mut game, 0, 20), None);
+        assert_eq!(
+            near(&mut proximity, &mut game, 1, 30),
+            Some(Alert::FirstContact)
+        );
+    }
+
+    #[test]
+    fn teammates_are_not_contacts() {
+        let mut proximity = Proximity::new(&ProximityConfig::default());
+        let mut game = game("Prospect-1", 3);
+        assert_eq!(near(&mut proximity, &mut game, 2, 0), None);
+        assert_eq!(
+            near(&mut proximity, &mut game, 3, 10),
+            Some(Alert::FirstContact)
+        );
+    }
+
+    #[test]
+    fn cooldown_suppresses_alerts() {
+        let mut proximity = Proximity::new(&ProximityConfig::default());
+        let mut game = game("Prospect-1", 1);
+        assert_eq!(
+            near(&mut proximity, &mut game, 1, 0),
+            Some(Alert::FirstContact)
+        );
+        assert_eq!(near(&mut proximity, &mut game, 2, 2), None);
+        // The contacts during the cooldown are the level of the next alert
+        assert_eq!(near(&mut proximity, &mut game, 2, 10), None);
+        assert_eq!(
+            near(&mut proximity, &mut game, 3, 12),
+            Some(Alert::MoreContacts)
+        );
+    }
+
+    #[test]
+    fn threshold_and_step_are_respected() {
+        let config = ProximityConfig {
+            threshold: 2,
+            step: 2,
+            cooldown: 0,
+            ..ProximityConfig::default()
+        };
+        let mut proximity = Proximity::new(&config);
+        let mut game = game("Prospect-1", 1);
+        assert_eq!(near(&mut proximity, &mut game, 1, 0), None);
+        assert_eq!(
+            near(&mut proximity, &mut game, 2, 1),
+            Some(Alert::FirstContact)
+        );
+        assert_eq!(near(&mut proximity, &mut game, 3, 2), None);
+        assert_eq!(
+            near(&mut proximity, &mut game, 4, 3),
+            Some(Alert::MoreContacts)
+        );
+    }
+
+    #[test]
+    fn players_going_away_lower_the_level() {
+        let config = ProximityConfig {
+            cooldown: 0,
+            ..ProximityConfig::default()
+        };
+        let mut proximity = Proximity::new(&config);
+        let mut game = game("Prospect-1", 1);
+        assert_eq!(
+            near(&mut proximity, &mut game, 3, 0),
+            Some(Alert::FirstContact)
+        );
+        assert_eq!(near(&mut proximity, &mut game, 1, 1), None);
+        assert_eq!(
+            near(&mut proximity, &mut game, 2, 2),
+            Some(Alert::MoreContacts)
+        );
+    }
+
+    #[test]
+    fn another_game_starts_without_contacts() {
+        let config = ProximityConfig {
+            cooldown: 0,
+            ..ProximityConfig::default()
+        };
+        let mut proximity = Proximity::new(&config);
+        let mut first = game("Prospect-1", 1);
+        let mut second = game("Prospect-2", 1);
+        assert_eq!(
+            near(&mut proximity, &mut first, 2, 0),
+            Some(Alert::FirstContact)
+        );
+        assert_eq!(
+            near(&mut proximity, &mut second, 2, 1),
+            Some(Alert::FirstContact)
+        );
+    }
+
+    #[test]
+    fn disabled_alerts_are_never_played() {
+        let config = ProximityConfig {
+            enabled: false,
+            ..ProximityConfig::default()
+        };
+        let mut proximity = Proximity::new(&config);
+        let mut game = game("Prospect-1", 1);
+        assert_eq!(near(&mut proximity, &mut game, 5, 0), None);
+    }
+}
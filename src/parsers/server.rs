@@ -22,28 +22,27 @@
 //! related to the game, such as starting a new session, exiting it and etc.
 
 use super::substring_between;
-use crate::objects::{Game, GameMap};
+use crate::objects::{Game, GameMap, InstanceId, MatchOptions, Region, StormClock, Timings};
 use crate::overlay::events;
 use crate::state::StateHolder;
 
 use winit::event_loop::EventLoopProxy;
 
-use std::ops::Sub;
 use std::sync;
 
 /// Parser struct that store some details about the server and current game instance,
 /// and is responsible for parsing information about the server and game.
 pub struct Parser {
     // Unique identifier for the game instance
-    instance_id: String,
+    instance_id: Option<InstanceId>,
     // Geographic region of the game
-    region: String,
+    region: Option<Region>,
     // Map of the game
     map: GameMap,
-    // Size of the party
-    party_size: usize,
-    // Time when the game instance was created
-    created_at: chrono::DateTime<chrono::Utc>,
+    // Options of the match
+    options: MatchOptions,
+    // Storm clock which estimates when the game instance was created
+    clock: StormClock,
     // State of the game, whether it is on hold or not
     hold: bool,
 }
@@ -52,11 +51,11 @@ impl Default for Parser {
     /// Constructs a new Parser object with default values.
     fn default() -> Self {
         Self {
-            instance_id: String::new(),
-            region: String::new(),
-            map: GameMap::TharisIsland(crate::objects::NORMAL.clone()),
-            party_size: 0,
-            created_at: chrono::DateTime::default(),
+            instance_id: None,
+            region: None,
+            map: GameMap::TharisIsland(Timings::default()),
+            options: MatchOptions::default(),
+            clock: StormClock::default(),
             hold: false,
         }
     }
@@ -105,11 +104,28 @@ impl super::Parser for Parser {
                                 )))
                                 .unwrap();
                         } else {
-                            // If the game is a match
-                            self.hold = true;
-                            // Extract and store the instance ID and region of the game
-                            self.instance_id = substring_between(t, "sessionId [", "]").unwrap();
-                            self.region = substring_between(t, "region [", "]").unwrap();
+                            // If the game is a match, extract the instance ID and region of it
+                            let instance_id = substring_between(t, "sessionId [", "]")
+                                .ok_or_else(|| "no instance id".to_string())
+                                .and_then(|id| id.parse::<InstanceId>());
+                            let region = substring_between(t, "region [", "]")
+                                .ok_or_else(|| "no region".to_string())
+                                .and_then(|region| region.parse::<Region>());
+
+                            match (instance_id, region) {
+                                (Ok(instance_id), Ok(region)) => {
+                                    self.hold = true;
+                                    // Store the instance ID and region of the game
+                                    self.instance_id = Some(instance_id);
+                                    self.region = Some(region);
+                                    // Start a new storm clock, the previous one belongs to
+                                    // another server
+                                    self.clock = StormClock::default();
+                                }
+                                (Err(e), _) | (_, Err(e)) => {
+                                    log::error!("Cannot parse: {} ({})", text, e);
+                                }
+                            }
                         }
                     } else {
                         // If parsing fails, log an error
@@ -118,21 +134,24 @@ impl super::Parser for Parser {
                 }
                 // If the event indicates forced transition
                 t if t.starts_with("Forcing transition to match") => {
-                    // Parse and store the size of the party
-                    let size = substring_between(t, "SquadSize=", "?");
-                    self.party_size = size.map_or(1, |s| s.parse().unwrap());
+                    // Parse and store the options of the match
+                    self.options = MatchOptions::from_url(t);
+                    if !self.options.unknown.is_empty() {
+                        log::debug!("Unknown match options: {:?}", self.options.unknown);
+                    }
                 }
                 _ => (),
             },
             // If the game is on hold and a handshake is occurring
             "LogHandshake" if self.hold && text.starts_with("SendChallengeResponse") => {
-                // Calculate the creation time of the game instance
-                let seconds_since_start = substring_between(text, "Timestamp: ", ".")
-                    .unwrap()
-                    .parse::<i64>()
-                    .unwrap()
-                    - 5;
-                self.created_at = time.sub(chrono::Duration::seconds(seconds_since_start));
+                // Anchor the storm clock to the server uptime reported by the handshake
+                let uptime = substring_between(text, "Timestamp: ", ".")
+                    .and_then(|uptime| uptime.parse::<i64>().ok());
+                if let Some(uptime) = uptime {
+                    self.clock.add_handshake(time, uptime);
+                } else {
+                    log::error!("Cannot parse: {}", text);
+                }
             }
             // If the game is on hold and the player is welcomed by the server
             "LogNet" if self.hold && text.starts_with("Welcomed by server") => {
@@ -143,23 +162,32 @@ impl super::Parser for Parser {
                     self.map = map;
                 }
 
+                self.hold = false;
+
+                // The instance ID and region are always parsed before the hold
+                let (instance_id, region) = match (self.instance_id.clone(), self.region.clone()) {
+                    (Some(instance_id), Some(region)) => (instance_id, region),
+                    _ => return,
+                };
+
                 // Create a new game instance
                 let game = Game::new(
-                    self.instance_id.clone(),
-                    self.region.clone(),
+                    instance_id,
+                    region,
                     self.map.clone(),
-                    self.created_at,
-                    self.party_size,
+                    self.clock.clone(),
+                    self.options.clone(),
+                    state.get_build(),
+                    time,
                 );
-                self.hold = false;
 
                 // Log the new game instance
                 log::info!("==================================================");
-                log::info!("New instance: {:?}", game.name);
+                log::info!("New instance: {:?} ({})", game.name, game.region);
                 log::info!("==================================================");
 
-                // Update global state (started new game)
-                state.set_game(game.clone());
+                // Update global state (started new game), a rejoined game keeps its meteors
+                let game = state.set_game(game);
                 // Send an updated to the game state in `Overlay`
                 let sender = event_loop_proxy.lock().unwrap();
                 sender
@@ -181,12 +209,14 @@ impl super::Parser for Parser {
 ///
 /// # Returns
 ///
-/// * `Option<GameMap>` - Corresponding GameMap variant if the map string is recognized, None otherwise.
-fn parse_map(map: String) -> Option<GameMap> {
+/// * `Option<GameMap>` - Corresponding GameMap variant with the timing profile of the map if the
+///   map string is recognized, None otherwise.
+pub fn parse_map(map: String) -> Option<GameMap> {
+    let timings = Timings::for_map(&map);
     match map.as_str() {
-        "MAP01" => Some(GameMap::BrightSands(crate::objects::NORMAL.clone())),
-        "MAP02" => Some(GameMap::CrescentFalls(crate::objects::NORMAL.clone())),
-        "AlienCaverns" => Some(GameMap::TharisIsland(crate::objects::THARIS.clone())),
+        "MAP01" => Some(GameMap::BrightSands(timings)),
+        "MAP02" => Some(GameMap::CrescentFalls(timings)),
+        "AlienCaverns" => Some(GameMap::TharisIsland(timings)),
         _ => None,
     }
 }
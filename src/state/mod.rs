@@ -20,7 +20,7 @@
 
 //! This module contains global state structure.
 
-use crate::objects::Game;
+use crate::objects::{Game, GameBuild};
 
 use std::collections::LinkedList;
 use std::sync::Mutex;
@@ -32,6 +32,8 @@ pub struct StateHolder {
     games: Mutex<LinkedList<Game>>,
     // Flag saying player in game now or not
     in_game: Mutex<bool>,
+    // The build of the game client found in the log header
+    build: Mutex<Option<GameBuild>>,
 }
 
 impl StateHolder {
@@ -48,6 +50,7 @@ impl StateHolder {
         StateHolder {
             games: Mutex::new(LinkedList::new()),
             in_game: Mutex::new(false),
+            build: Mutex::new(None),
         }
     }
 
@@ -75,7 +78,8 @@ impl StateHolder {
         }
     }
 
-    /// Sets the current game and marks the player as in game.
+    /// Sets the current game and marks the player as in game. A game already played on the same
+    /// instance is continued, so the meteor showers on the server are kept when it is rejoined.
     ///
     /// # Arguments
     ///
@@ -83,8 +87,8 @@ impl StateHolder {
     ///
     /// # Returns
     ///
-    /// * None
-    pub fn set_game(&self, game: Game) {
+    /// * Game - The game which became the current one.
+    pub fn set_game(&self, game: Game) -> Game {
         let mut in_game = self.in_game.lock().unwrap();
         *in_game = true;
 
@@ -97,7 +101,9 @@ impl StateHolder {
             .iter()
             .find(|g| g.instance_id == game.instance_id)
             .cloned();
-        games.push_front(existing_game.unwrap_or(game));
+        let game = existing_game.unwrap_or(game);
+        games.push_front(game.clone());
+        game
     }
 
     /// Returns a reference to the Mutex protecting the LinkedList of games.
@@ -113,6 +119,32 @@ impl StateHolder {
         &self.games
     }
 
+    /// Sets the build of the game client.
+    ///
+    /// # Arguments
+    ///
+    /// * `build` - The build found in the log header.
+    ///
+    /// # Returns
+    ///
+    /// * None
+    pub fn set_build(&self, build: GameBuild) {
+        *self.build.lock().unwrap() = Some(build);
+    }
+
+    /// Returns the build of the game client.
+    ///
+    /// # Arguments
+    ///
+    /// * None
+    ///
+    /// # Returns
+    ///
+    /// * Some with the build, or None if it was not found in the log header yet.
+    pub fn get_build(&self) -> Option<GameBuild> {
+        self.build.lock().unwrap().clone()
+    }
+
     /// Returns true if the player is in a game, false otherwise.
     ///
     /// # Arguments
@@ -150,3 +182,39 @@ impl StateHolder {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{GameMap, InstanceId, MatchOptions, Region, StormClock, Timings};
+
+    fn game(instance_id: &str) -> Game {
+        Game::new(
+            instance_id.parse::<InstanceId>().unwrap(),
+            Region::Europe,
+            GameMap::BrightSands(Timings::for_map("Map01")),
+            StormClock::default(),
+            MatchOptions::default(),
+            None,
+            chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn rejoined_game_keeps_its_meteors() {
+        let state = StateHolder::new();
+        state.set_game(game("Prospect-1"));
+        let time = chrono::DateTime::from_timestamp(1_700_000_300, 0).unwrap();
+        state
+            .get_games()
+            .lock()
+            .unwrap()
+            .front_mut()
+            .unwrap()
+            .record_meteors(time);
+        state.leave_game();
+
+        assert_eq!(state.set_game(game("Prospect-1")).meteors().len(), 1);
+        assert_eq!(state.set_game(game("Prospect-2")).meteors().len(), 0);
+    }
+}
@@ -20,6 +20,33 @@
 
 //! This module contains some useful functions.
 
+use lazy_static::lazy_static;
+
+use std::sync::RwLock;
+
+lazy_static! {
+    /// The virtual time, which replaces the system clock when set, e.g. for headless rendering.
+    static ref VIRTUAL_NOW: RwLock<Option<chrono::DateTime<chrono::Utc>>> = RwLock::new(None);
+}
+
+/// Returns the current time used by the overlay.
+///
+/// # Return
+///
+/// This function will return the virtual time if it is set, otherwise the system time.
+pub fn now() -> chrono::DateTime<chrono::Utc> {
+    VIRTUAL_NOW.read().unwrap().unwrap_or_else(chrono::Utc::now)
+}
+
+/// Sets or clears the virtual time.
+///
+/// # Arguments
+///
+/// * `time` - The virtual time, or None to use the system time again.
+pub fn set_virtual_now(time: Option<chrono::DateTime<chrono::Utc>>) {
+    *VIRTUAL_NOW.write().unwrap() = time;
+}
+
 /// Generate a fake name consisting of a color and an animal.
 ///
 /// # Arguments
@@ -74,65 +101,3 @@ pub fn fake_name(mut rng: rand::rngs::StdRng) -> String {
     // Return the combined color and animal as the fake name.
     format!("{} {}", color, animal)
 }
-
-/// Emit a beep sound if less than 60 seconds have passed since a specified time.
-///
-/// # Arguments
-///
-/// * `freq` - The frequency of the beep.
-/// * `duration` - The duration of the beep.
-/// * `time` - The time to compare with the current time.
-pub fn beep(freq: u32, duration: u64, time: chrono::DateTime<chrono::Utc>) {
-    // Import necessary traits from the cpal crate.
-    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-    // Import the Sub trait from the std::ops module.
-    use std::ops::Sub;
-
-    // If less than 60 seconds have passed since the specified time...
-    if chrono::Utc::now().sub(time) < chrono::Duration::seconds(60) {
-        // Set up the audio output stream.
-        let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .expect("no output device available");
-        let config = device.default_output_config().unwrap();
-        let config: cpal::StreamConfig = config.into();
-        let sample_rate = config.sample_rate.0 as f32;
-        let channels = config.channels as usize;
-
-        // Define a function to handle errors.
-        let err_fn = |err| eprintln!("an error occurred on the output audio stream: {}", err);
-
-        // Set up the sine wave generator.
-        let sample_duration = 1.0 / sample_rate;
-        let mut sample_clock = 0f32;
-        let mut next_value = move || {
-            let value = (sample_clock * freq as f32 * 2.0 * std::f32::consts::PI).sin() * 0.5;
-            sample_clock = (sample_clock + sample_duration) % 1.0;
-            value
-        };
-
-        // Build and start the output stream.
-        let stream = device
-            .build_output_stream(
-                &config,
-                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                    for frame in data.chunks_mut(channels) {
-                        let value: f32 = next_value();
-                        for sample in frame.iter_mut() {
-                            *sample = value;
-                        }
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .unwrap();
-        stream.play().unwrap();
-
-        // Sleep for the duration of the beep.
-        std::thread::sleep(std::time::Duration::from_millis(duration));
-        // Pause the output stream.
-        stream.pause().unwrap();
-    }
-}
@@ -0,0 +1,112 @@
+// Copyright (c) 2023
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Golden image tests of the overlay. Each scene in `tests/scenes` is rendered with the default
+//! configuration and compared with its image in `tests/golden`. Run the tests with
+//! `UPDATE_GOLDEN=1` to replace the images after an intended change of the look.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The largest difference of a color channel which still counts as the same pixel, for the
+/// rounding of the rasterizer on other platforms.
+const CHANNEL_TOLERANCE: u8 = 8;
+
+/// The largest share of the pixels which may differ.
+const PIXEL_TOLERANCE: f64 = 0.001;
+
+/// Returns the path of a file in the `tests` directory.
+fn tests_path(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(path)
+}
+
+/// Reads a PNG image.
+///
+/// # Returns
+///
+/// * The width, the height and the RGBA pixels of the image.
+fn read_png(path: &Path) -> (u32, u32, Vec<u8>) {
+    let file = std::fs::File::open(path).unwrap_or_else(|e| panic!("{:?}: {}", path, e));
+    let mut reader = png::Decoder::new(file).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgba, "{:?}", path);
+    data.truncate(info.buffer_size());
+    (info.width, info.height, data)
+}
+
+/// Renders the scene and compares it with its golden image.
+fn check_scene(name: &str) {
+    let dir = std::env::temp_dir().join(format!("cycle_log_parser_golden_{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let actual = dir.join(format!("{}.png", name));
+
+    // There is no configuration in the fresh directory, so the defaults are used
+    let status = Command::new(env!("CARGO_BIN_EXE_cycle_log_parser"))
+        .env("PROSPECT_CONFIG", dir.join("config.toml"))
+        .arg("--screenshot")
+        .arg(&actual)
+        .arg("--scene")
+        .arg(tests_path(&format!("scenes/{}.toml", name)))
+        .args(["800", "600"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "the screenshot of {} failed", name);
+
+    let golden = tests_path(&format!("golden/{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::copy(&actual, &golden).unwrap();
+        return;
+    }
+
+    let (width, height, expected) = read_png(&golden);
+    let (actual_width, actual_height, pixels) = read_png(&actual);
+    assert_eq!((actual_width, actual_height), (width, height));
+    let different = expected
+        .chunks(4)
+        .zip(pixels.chunks(4))
+        .filter(|(a, b)| {
+            a.iter()
+                .zip(b.iter())
+                .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    let share = different as f64 / (width * height) as f64;
+    assert!(
+        share <= PIXEL_TOLERANCE,
+        "{} pixels of {} differ from the golden image, see {:?}",
+        different,
+        name,
+        actual
+    );
+}
+
+#[test]
+fn match_with_death_recap() {
+    check_scene("match");
+}
+
+#[test]
+fn lobby_summary() {
+    check_scene("lobby");
+}